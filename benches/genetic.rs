@@ -23,7 +23,7 @@ extern crate test;
 extern crate spiril;
 extern crate rand;
 
-use spiril::unit::Unit;
+use spiril::unit::ScalarUnit;
 use rand::Rng;
 
 struct SudokuUnit {
@@ -31,7 +31,7 @@ struct SudokuUnit {
     answer: Vec<usize>, // 9x9 grid
 }
 
-impl Unit for SudokuUnit {
+impl ScalarUnit for SudokuUnit {
     fn fitness(&self) -> f64 {
         let mut score = 1.0_f64;
 
@@ -63,6 +63,10 @@ impl Unit for SudokuUnit {
     }
 
     fn breed_with(&self, other: &SudokuUnit) -> SudokuUnit {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &SudokuUnit, rng: &mut R) -> SudokuUnit {
         // Even rows taken from self, odd rows taken from other.
         // Mutations applied at random.
         let mut new_unit: SudokuUnit = SudokuUnit {
@@ -73,7 +77,7 @@ impl Unit for SudokuUnit {
         (0_usize..81_usize)
             .filter(|x| self.sudoku[*x] == 0)
             .map(|x| {
-                if rand::thread_rng().gen_range(0, 1) == 1 {
+                if rng.gen_range(0, 1) == 1 {
                     new_unit.answer[x] = other.answer[x];
                 }
                 new_unit.answer[x]
@@ -81,9 +85,9 @@ impl Unit for SudokuUnit {
             .last();
 
         loop {
-            let i = rand::thread_rng().gen_range(0, 81);
+            let i = rng.gen_range(0, 81);
             if self.sudoku[i] == 0 {
-                new_unit.answer[i] = rand::thread_rng().gen_range(1, 10);
+                new_unit.answer[i] = rng.gen_range(1, 10);
                 break;
             }
         }
@@ -96,7 +100,7 @@ impl Unit for SudokuUnit {
 mod tests {
     use test::Bencher;
     use spiril::population::Population;
-    use spiril::unit::Unit;
+    use spiril::termination::{MaxGenerations, TargetFitness};
     use rand::{Rng, SeedableRng, StdRng};
     use SudokuUnit;
 
@@ -152,11 +156,9 @@ mod tests {
 
             assert_eq!(Population::new(units)
                 .set_size(1000)
+                .set_termination(TargetFitness::new(1.0))
                 .epochs(5000)
-                .finish()
-                .first()
-                .unwrap()
-                .fitness(), 1.0);
+                .best_fitness(), Some(1.0));
         });
     }
 
@@ -199,11 +201,90 @@ mod tests {
 
             assert_eq!(Population::new(units)
                 .set_size(1000)
+                .set_termination(TargetFitness::new(1.0))
                 .epochs(5000)
-                .finish()
-                .first()
-                .unwrap()
-                .fitness(), 1.0);
+                .best_fitness(), Some(1.0));
         });
     }
+
+    /// Evolves a fixed number of generations of sudoku units, where the fitness
+    /// function is cheap, across `n_processes` threads to measure the overhead
+    /// of distributing work between them.
+    #[cfg_attr(rustfmt, rustfmt_skip)]
+    fn bench_parallel(b: &mut Bencher, n_processes: u32) {
+        let test_doku: Vec<usize> = vec![
+            7, 2, 6,   0, 9, 3,   8, 1, 5,
+            3, 0, 5,   7, 2, 8,   9, 0, 6,
+            4, 8, 0,   6, 0, 1,   2, 3, 7,
+
+            8, 5, 0,   1, 4, 0,   6, 0, 0,
+            0, 7, 3,   9, 0, 5,   1, 2, 4,
+            9, 4, 1,   0, 6, 0,   0, 5, 8,
+
+            1, 9, 0,   8, 3, 0,   0, 7, 2,
+            5, 6, 7,   2, 1, 4,   3, 0, 0,
+            2, 0, 8,   5, 0, 0,   4, 6, 1,
+        ];
+
+        b.iter(|| {
+            let seed: &[_] = &[0];
+            let mut init_rng: StdRng = SeedableRng::from_seed(seed);
+            let units: Vec<SudokuUnit> = (0..5000)
+                .map(|_| {
+                    SudokuUnit {
+                        sudoku: test_doku.clone(),
+                        answer: test_doku
+                            .iter()
+                            .map(|x| if *x == 0 {
+                                init_rng.gen_range(1, 10)
+                            } else {
+                                *x
+                            })
+                            .collect(),
+                    }
+                })
+                .collect();
+
+            Population::new(units)
+                .set_size(5000)
+                .set_termination(MaxGenerations::new(20))
+                .epochs_parallel(20, n_processes)
+                .best_fitness()
+        });
+    }
+
+    #[bench]
+    fn bench_parallel_1(b: &mut Bencher) {
+        bench_parallel(b, 1);
+    }
+
+    #[bench]
+    fn bench_parallel_2(b: &mut Bencher) {
+        bench_parallel(b, 2);
+    }
+
+    #[bench]
+    fn bench_parallel_4(b: &mut Bencher) {
+        bench_parallel(b, 4);
+    }
+
+    #[bench]
+    fn bench_parallel_8(b: &mut Bencher) {
+        bench_parallel(b, 8);
+    }
+
+    #[bench]
+    fn bench_parallel_16(b: &mut Bencher) {
+        bench_parallel(b, 16);
+    }
+
+    #[bench]
+    fn bench_parallel_32(b: &mut Bencher) {
+        bench_parallel(b, 32);
+    }
+
+    #[bench]
+    fn bench_parallel_64(b: &mut Bencher) {
+        bench_parallel(b, 64);
+    }
 }
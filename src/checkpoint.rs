@@ -0,0 +1,107 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+//! Saving and restoring populations, available with the `serde` feature.
+//!
+//! A checkpoint contains the units of a population along with their cached
+//! fitness, the configuration factors, the objective, the random seed, the
+//! generation counter and the evolution report. Since each generation draws
+//! from a generator derived from the seed and the generation number, a restored
+//! population continues exactly as the original would have.
+//!
+//! Checkpoints are written in the binary format of `bincode`, which keeps every
+//! fitness exactly, including NaN and infinite values.
+//!
+//! Selection strategies, termination conditions, observers, the hall of fame,
+//! the thread pool and the failure and non-finite policies are not saved, and
+//! must be set again on a restored population.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use bincode;
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// An error encountered while saving or restoring a checkpoint.
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// The checkpoint file could not be read or written.
+    Io(io::Error),
+
+    /// The checkpoint could not be encoded or decoded.
+    Format(bincode::Error),
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            CheckpointError::Io(ref err) => write!(f, "checkpoint io error: {}", err),
+            CheckpointError::Format(ref err) => write!(f, "checkpoint format error: {}", err),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            CheckpointError::Io(ref err) => Some(err),
+            CheckpointError::Format(ref err) => Some(err),
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(err: io::Error) -> Self {
+        CheckpointError::Io(err)
+    }
+}
+
+impl From<bincode::Error> for CheckpointError {
+    fn from(err: bincode::Error) -> Self {
+        CheckpointError::Format(err)
+    }
+}
+
+/// Writes a value to a temporary file next to `path` before renaming it, so
+/// that a crash part way through never leaves a truncated checkpoint behind.
+pub(crate) fn write_atomic<S: Serialize>(path: &Path, value: &S) -> Result<(), CheckpointError> {
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    {
+        let mut writer = BufWriter::new(File::create(&tmp_path)?);
+        bincode::serialize_into(&mut writer, value)?;
+        writer.flush()?;
+        writer.get_ref().sync_all()?;
+    }
+
+    fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+/// Reads a value from a checkpoint file.
+pub(crate) fn read<D: DeserializeOwned>(path: &Path) -> Result<D, CheckpointError> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(bincode::deserialize_from(reader)?)
+}
@@ -0,0 +1,377 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! Calculating the fitness of units and handling units whose fitness cannot be
+//! calculated, along with a pool of threads for calculating fitness and
+//! breeding new units in parallel, which can be created once and shared between
+//! any number of populations.
+
+use unit::Unit;
+use population::LazyUnit;
+use fitness::{self, Fitness};
+
+use std::any::Any;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+
+/// Determines what happens to a unit whose fitness calculation returns an
+/// error or panics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// End the run with an `EvaluationError`. The fitnesses already calculated
+    /// are kept, and so a later run only retries the units that failed. This
+    /// is the default.
+    Abort,
+
+    /// Give the unit the fitness of the weakest unit of its generation that
+    /// did not fail.
+    WorstFitness,
+
+    /// Remove the unit from its generation, which is then refilled by the next
+    /// epoch.
+    Discard,
+
+    /// Calculate the fitness of the unit again up to this many more times,
+    /// ending the run with an `EvaluationError` if it never succeeds.
+    Retry(u32),
+}
+
+/// The reason a fitness calculation failed.
+#[derive(Debug)]
+pub enum Failure {
+    /// `Unit::try_fitness` returned an error.
+    Error(Box<dyn Error + Send + Sync>),
+
+    /// The fitness calculation panicked with this message.
+    Panic(String),
+
+    /// The fitness is NaN or infinite, and the `NonFinitePolicy` of the
+    /// population rejects it. A fitness that is not a single number, such as a
+    /// tuple containing NaN, is given as NaN.
+    NonFinite(f64),
+}
+
+impl Failure {
+    fn from_panic(payload: Box<dyn Any + Send>) -> Self {
+        let message = match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(payload) => match payload.downcast::<&str>() {
+                Ok(message) => message.to_string(),
+                Err(_) => "unknown panic".to_string(),
+            },
+        };
+        Failure::Panic(message)
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Failure::Error(ref err) => write!(f, "{}", err),
+            Failure::Panic(ref message) => write!(f, "panicked: {}", message),
+            Failure::NonFinite(fitness) => write!(f, "fitness is {}", fitness),
+        }
+    }
+}
+
+/// An error that ended a run because the fitness of units could not be
+/// calculated.
+#[derive(Debug)]
+pub struct EvaluationError {
+    /// The generation containing the failed units.
+    pub generation: u32,
+
+    /// The number of units that failed.
+    pub failed: usize,
+
+    /// The reason the first of the failed units failed.
+    pub failure: Failure,
+}
+
+impl fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "fitness of {} unit(s) failed in generation {}: {}",
+            self.failed,
+            self.generation,
+            self.failure
+        )
+    }
+}
+
+impl Error for EvaluationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self.failure {
+            Failure::Error(ref err) => Some(&**err),
+            Failure::Panic(_) | Failure::NonFinite(_) => None,
+        }
+    }
+}
+
+/// Calculates the fitness of a unit, catching any panic, and optionally
+/// rejecting a fitness that is NaN or infinite.
+fn try_fitness<T: Unit>(unit: &mut LazyUnit<T>, reject_non_finite: bool) -> Result<(), Failure> {
+    if unit.lazy_fitness.is_some() {
+        return Ok(());
+    }
+    let fitness = match panic::catch_unwind(AssertUnwindSafe(|| unit.unit.try_fitness())) {
+        Ok(Ok(fitness)) => fitness,
+        Ok(Err(err)) => return Err(Failure::Error(err)),
+        Err(payload) => return Err(Failure::from_panic(payload)),
+    };
+    if reject_non_finite && !fitness::is_finite(&fitness) {
+        return Err(Failure::NonFinite(fitness.to_f64().unwrap_or(f64::NAN)));
+    }
+    unit.lazy_fitness = Some(fitness);
+    Ok(())
+}
+
+/// A unit awaiting evaluation, along with the reason it failed if it did.
+struct Pending<'a, T: Unit + 'a> {
+    unit: &'a mut LazyUnit<T>,
+    failure: Option<Failure>,
+}
+
+impl<'a, T: Unit + 'a> Pending<'a, T> {
+    fn evaluate(&mut self, attempts: u32, reject_non_finite: bool) {
+        for _ in 0..attempts {
+            match try_fitness(self.unit, reject_non_finite) {
+                Ok(_) => {
+                    self.failure = None;
+                    return;
+                }
+                Err(failure) => self.failure = Some(failure),
+            }
+        }
+    }
+}
+
+/// Calculates the fitness of each unit that has not been evaluated yet, on the
+/// thread pool if given and otherwise on the current thread, making up to
+/// `attempts` (> 0) attempts for each unit. Units are evaluated in place, and
+/// so their order is preserved. Returns the failures of units that never
+/// succeeded, in the order of the units, and leaves those units unevaluated.
+pub(crate) fn evaluate<T: Unit>(
+    units: &mut [LazyUnit<T>],
+    thread_pool: Option<&ThreadPool>,
+    attempts: u32,
+    reject_non_finite: bool,
+) -> Vec<Failure> {
+    let mut pending: Vec<Pending<T>> = units
+        .iter_mut()
+        .filter(|u| u.lazy_fitness.is_none())
+        .map(|unit| Pending {
+            unit,
+            failure: None,
+        })
+        .collect();
+
+    match thread_pool {
+        Some(thread_pool) => {
+            thread_pool.for_each(&mut pending, |p| p.evaluate(attempts, reject_non_finite))
+        }
+        None => {
+            for p in &mut pending {
+                p.evaluate(attempts, reject_non_finite);
+            }
+        }
+    }
+
+    pending.into_iter().filter_map(|p| p.failure).collect()
+}
+
+//------------------------------------------------------------------------------
+
+/// The number of chunks each thread is given, which trades the overhead of
+/// claiming a chunk against the balance of work between threads.
+const CHUNKS_PER_THREAD: usize = 8;
+
+/// ThreadPool is a set of worker threads that calculate the fitness of units,
+/// and optionally breed units, in parallel. Threads are spawned once when the
+/// pool is created and reused for every generation of every population the
+/// pool is given to, and are shut down once the pool and all of its clones have
+/// been dropped.
+///
+/// Cloning a pool is cheap and shares the same threads. A pool can be used by
+/// several populations at once from different threads, but must not be used
+/// from within a fitness function that is itself run on the pool.
+#[derive(Clone)]
+pub struct ThreadPool {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    senders: Vec<Sender<Arc<Task>>>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// Creates a pool of `n_threads` (> 0) worker threads.
+    pub fn new(n_threads: usize) -> Self {
+        assert!(n_threads > 0);
+
+        let (senders, handles) = (0..n_threads)
+            .map(|index| {
+                let (tx, rx) = channel();
+                (tx, thread::spawn(move || work(index, &rx)))
+            })
+            .unzip();
+
+        ThreadPool { inner: Arc::new(Inner { senders, handles }) }
+    }
+
+    /// Returns the number of worker threads in the pool.
+    pub fn n_threads(&self) -> usize {
+        self.inner.senders.len()
+    }
+
+    /// Calls `f` on each item across the threads of the pool. The items are
+    /// split into chunks that are distributed evenly between the threads, and a
+    /// thread that finishes its own chunks steals the remaining chunks of the
+    /// others.
+    pub(crate) fn for_each<I, F>(&self, items: &mut [I], f: F)
+    where
+        I: Send,
+        F: Fn(&mut I) + Sync,
+    {
+        if items.is_empty() {
+            return;
+        }
+
+        let n_threads = self.n_threads();
+        let chunk_size = (items.len() / (n_threads * CHUNKS_PER_THREAD)).max(1);
+
+        // Each chunk is only ever claimed once, and so its lock is never
+        // contended.
+        let chunks: Vec<Mutex<&mut [I]>> = items.chunks_mut(chunk_size).map(Mutex::new).collect();
+
+        let queues: Vec<Queue> = (0..n_threads)
+            .map(|i| {
+                Queue {
+                    next: AtomicUsize::new(i * chunks.len() / n_threads),
+                    end: (i + 1) * chunks.len() / n_threads,
+                }
+            })
+            .collect();
+
+        self.broadcast(&|thread| for offset in 0..n_threads {
+            let queue = &queues[(thread + offset) % n_threads];
+            while let Some(index) = queue.claim() {
+                for item in chunks[index].lock().unwrap().iter_mut() {
+                    f(item);
+                }
+            }
+        });
+    }
+
+    /// Runs `f` once on every worker thread with the index of the thread, and
+    /// blocks until every call has returned.
+    fn broadcast(&self, f: &(dyn Fn(usize) + Sync)) {
+        // Workers only call `f` before reporting back, and this function does
+        // not return until every worker has reported back, including when `f`
+        // panics, so the lifetime of `f` can safely be erased.
+        let f: &'static (dyn Fn(usize) + Sync) = unsafe { mem::transmute(f) };
+        let task = Arc::new(Task {
+            f: f as *const _,
+            remaining: Mutex::new(self.n_threads()),
+            finished: Condvar::new(),
+            panicked: AtomicBool::new(false),
+        });
+
+        for sender in &self.inner.senders {
+            sender.send(task.clone()).expect("thread pool worker has shut down");
+        }
+
+        let mut remaining = task.remaining.lock().unwrap();
+        while *remaining > 0 {
+            remaining = task.finished.wait(remaining).unwrap();
+        }
+
+        if task.panicked.load(Ordering::SeqCst) {
+            panic!("thread pool worker panicked");
+        }
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        // Closing the channels ends the worker loops.
+        self.senders.clear();
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// A function broadcast to every worker thread.
+struct Task {
+    f: *const (dyn Fn(usize) + Sync),
+    remaining: Mutex<usize>,
+    finished: Condvar,
+    panicked: AtomicBool,
+}
+
+// The function is `Sync` and is only called while `ThreadPool::broadcast` keeps
+// it alive.
+unsafe impl Send for Task {}
+unsafe impl Sync for Task {}
+
+/// The loop of a worker thread, which runs each task it receives until the
+/// pool is dropped.
+fn work(index: usize, tasks: &Receiver<Arc<Task>>) {
+    for task in tasks {
+        let f = unsafe { &*task.f };
+        if panic::catch_unwind(AssertUnwindSafe(|| f(index))).is_err() {
+            task.panicked.store(true, Ordering::SeqCst);
+        }
+
+        let mut remaining = task.remaining.lock().unwrap();
+        *remaining -= 1;
+        task.finished.notify_all();
+    }
+}
+
+/// A contiguous range of chunks owned by one thread, claimed from the front by
+/// both the owner and any thread that has run out of its own work.
+struct Queue {
+    next: AtomicUsize,
+    end: usize,
+}
+
+impl Queue {
+    fn claim(&self) -> Option<usize> {
+        let index = self.next.fetch_add(1, Ordering::Relaxed);
+        if index < self.end {
+            Some(index)
+        } else {
+            None
+        }
+    }
+}
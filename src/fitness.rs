@@ -0,0 +1,187 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! Fitness values, the direction in which they are optimised, and the handling
+//! of fitness values that cannot be ranked normally.
+
+use std::cmp::Ordering;
+use std::fmt::Debug;
+
+/// Fitness is a value by which units are ranked, as returned by
+/// `Unit::fitness`. It is implemented for the primitive number types, where
+/// `f64` is the usual choice, and for tuples and vectors of fitness values,
+/// which are ranked lexicographically such that earlier elements take
+/// priority.
+///
+/// A fitness that is not comparable with itself, such as NaN, is ranked below
+/// every other fitness.
+pub trait Fitness: PartialOrd + Clone + Debug + Send + Sync {
+    /// Returns the fitness as a number, which is used for the mean and standard
+    /// deviation of generation stats and by selection strategies that weigh
+    /// units by their fitness. Fitness that is not a single number returns
+    /// `None`, in which case those strategies weigh units by their rank
+    /// instead. Defaults to `None`.
+    fn to_f64(&self) -> Option<f64> {
+        None
+    }
+}
+
+macro_rules! impl_numeric_fitness {
+    ($($t:ty),*) => {
+        $(
+            impl Fitness for $t {
+                #[allow(clippy::unnecessary_cast)]
+                fn to_f64(&self) -> Option<f64> {
+                    Some(*self as f64)
+                }
+            }
+        )*
+    };
+}
+
+impl_numeric_fitness!(f64, f32, i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<A: Fitness, B: Fitness> Fitness for (A, B) {}
+impl<A: Fitness, B: Fitness, C: Fitness> Fitness for (A, B, C) {}
+impl<A: Fitness, B: Fitness, C: Fitness, D: Fitness> Fitness for (A, B, C, D) {}
+impl<F: Fitness> Fitness for Vec<F> {}
+
+/// Returns false if a fitness is not comparable with itself, such as NaN.
+fn is_ranked<F: PartialOrd>(fitness: &F) -> bool {
+    fitness.partial_cmp(fitness).is_some()
+}
+
+/// Returns false if a fitness is NaN or infinite, or is otherwise not
+/// comparable with itself.
+pub(crate) fn is_finite<F: Fitness>(fitness: &F) -> bool {
+    is_ranked(fitness) && fitness.to_f64().is_none_or(f64::is_finite)
+}
+
+//------------------------------------------------------------------------------
+
+/// Whether a population seeks units with the greatest or the least fitness.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Objective {
+    /// A greater fitness is better. This is the default.
+    #[default]
+    Maximize,
+
+    /// A lesser fitness is better, for fitness functions that measure a cost
+    /// such as a distance or an error rate.
+    Minimize,
+}
+
+impl Objective {
+    /// Maps a numeric fitness onto a scale where greater is always better,
+    /// which is the scale selection strategies are given fitness on.
+    pub fn orient(self, fitness: f64) -> f64 {
+        match self {
+            Objective::Maximize => fitness,
+            Objective::Minimize => -fitness,
+        }
+    }
+
+    /// Returns true if fitness `a` is strictly better than fitness `b`.
+    pub fn is_better<F: Fitness>(self, a: &F, b: &F) -> bool {
+        self.cmp(a, b) == Ordering::Greater
+    }
+
+    /// Orders fitness `a` against fitness `b` by strength, where a fitness that
+    /// is not comparable with itself is weaker than every other fitness.
+    pub(crate) fn cmp<F: Fitness>(self, a: &F, b: &F) -> Ordering {
+        Ordered(a, self).cmp(&Ordered(b, self))
+    }
+
+    /// Returns the best of a number of fitnesses.
+    pub(crate) fn best<'a, F, I>(self, fitnesses: I) -> Option<&'a F>
+    where
+        F: Fitness + 'a,
+        I: IntoIterator<Item = &'a F>,
+    {
+        fitnesses.into_iter().max_by(|a, b| self.cmp(*a, *b))
+    }
+
+    /// Returns the worst of a number of fitnesses, ignoring any that are not
+    /// comparable with themselves.
+    pub(crate) fn worst<'a, F, I>(self, fitnesses: I) -> Option<&'a F>
+    where
+        F: Fitness + 'a,
+        I: IntoIterator<Item = &'a F>,
+    {
+        fitnesses
+            .into_iter()
+            .filter(|f| is_ranked(*f))
+            .min_by(|a, b| self.cmp(*a, *b))
+    }
+}
+
+/// Determines how units with a fitness of NaN or infinity are treated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonFinitePolicy {
+    /// Rank units with a fitness of NaN below every other unit, while
+    /// infinities are ranked as the best or worst possible fitness depending
+    /// on the objective. This is the default.
+    Worst,
+
+    /// Treat a fitness of NaN or infinity as a failed evaluation, which is
+    /// handled according to the `FailurePolicy` of the population.
+    Reject,
+
+    /// Panic with a description of the offending fitness.
+    Panic,
+}
+
+/// A fitness with a total order by strength under an objective, where a
+/// fitness that is not comparable with itself is weaker than every other
+/// fitness, used for ranking units.
+pub(crate) struct Ordered<'a, F: 'a>(pub(crate) &'a F, pub(crate) Objective);
+
+impl<'a, F: Fitness> PartialEq for Ordered<'a, F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'a, F: Fitness> Eq for Ordered<'a, F> {}
+
+impl<'a, F: Fitness> PartialOrd for Ordered<'a, F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'a, F: Fitness> Ord for Ordered<'a, F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (is_ranked(self.0), is_ranked(other.0)) {
+            (false, false) => Ordering::Equal,
+            (false, true) => Ordering::Less,
+            (true, false) => Ordering::Greater,
+            (true, true) => {
+                let ordering = self.0.partial_cmp(other.0).unwrap_or(Ordering::Equal);
+                match self.1 {
+                    Objective::Maximize => ordering,
+                    Objective::Minimize => ordering.reverse(),
+                }
+            }
+        }
+    }
+}
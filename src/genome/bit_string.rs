@@ -0,0 +1,377 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! A genome of bits packed into 64 bit words, for knapsack, feature selection
+//! and other subset problems.
+
+use unit::Unit;
+use genome::{sample_sorted, FitnessFn};
+
+use rand::{self, Rng};
+
+use std::fmt;
+use std::sync::Arc;
+
+const WORD_BITS: usize = 64;
+
+/// How the bits of two parents are combined into a child.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BitCrossover {
+    /// Takes the bits before a random point from one parent and the rest from
+    /// the other.
+    OnePoint,
+
+    /// Takes the bits between two random points from one parent and the rest
+    /// from the other. This is the default.
+    #[default]
+    TwoPoint,
+
+    /// Takes each bit from either parent with equal probability.
+    Uniform,
+
+    /// Cuts the parents at this many (> 0) random points and takes alternate
+    /// segments from each.
+    NPoint(usize),
+}
+
+//------------------------------------------------------------------------------
+
+/// BitGenome describes a string of bits of a fixed length, along with the
+/// fitness function and operators used to evolve it. Units are created from it
+/// with `unit`, `zeros` or `random_units`, and share its description.
+#[derive(Clone)]
+pub struct BitGenome {
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone)]
+struct Inner {
+    len: usize,
+    fitness: FitnessFn<BitString>,
+    crossover: BitCrossover,
+    mutation_rate: f64,
+}
+
+impl BitGenome {
+    /// Creates a genome of `len` (> 0) bits with the fitness function of a
+    /// string of bits. Units are bred with two-point crossover and flip one bit
+    /// per child on average.
+    pub fn new<F>(len: usize, fitness: F) -> Self
+    where
+        F: Fn(&BitString) -> f64 + Send + Sync + 'static,
+    {
+        assert!(len > 0);
+        BitGenome {
+            inner: Arc::new(Inner {
+                len,
+                fitness: Arc::new(fitness),
+                crossover: BitCrossover::default(),
+                mutation_rate: 1.0 / len as f64,
+            }),
+        }
+    }
+
+    /// Sets the crossover operator.
+    pub fn with_crossover(mut self, crossover: BitCrossover) -> Self {
+        if let BitCrossover::NPoint(n) = crossover {
+            assert!(n > 0);
+        }
+        Arc::make_mut(&mut self.inner).crossover = crossover;
+        self
+    }
+
+    /// Sets the probability (0 <= r <= 1) that each bit of a child is flipped.
+    pub fn with_mutation_rate(mut self, rate: f64) -> Self {
+        assert!((0.0..=1.0).contains(&rate));
+        Arc::make_mut(&mut self.inner).mutation_rate = rate;
+        self
+    }
+
+    /// Returns the number of bits of each unit.
+    pub fn len(&self) -> usize {
+        self.inner.len
+    }
+
+    /// Returns false, as a genome always has at least one bit.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Creates a unit with every bit clear.
+    pub fn zeros(&self) -> BitString {
+        BitString {
+            words: vec![0; words_for(self.inner.len)],
+            len: self.inner.len,
+            genome: self.clone(),
+        }
+    }
+
+    /// Creates a unit from its bits. Panics if the number of bits does not
+    /// match the genome.
+    pub fn unit<I: IntoIterator<Item = bool>>(&self, bits: I) -> BitString {
+        let mut unit = self.zeros();
+        let mut len = 0;
+        for (i, bit) in bits.into_iter().enumerate() {
+            assert!(i < unit.len, "too many bits for the genome");
+            unit.set(i, bit);
+            len += 1;
+        }
+        assert_eq!(len, unit.len, "too few bits for the genome");
+        unit
+    }
+
+    /// Creates `n` units where each bit is set with a probability of one half.
+    pub fn random_units<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<BitString> {
+        (0..n)
+            .map(|_| {
+                let mut unit = self.zeros();
+                for word in &mut unit.words {
+                    *word = rng.gen();
+                }
+                unit.clear_unused();
+                unit
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for BitGenome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("BitGenome")
+            .field("len", &self.inner.len)
+            .field("crossover", &self.inner.crossover)
+            .field("mutation_rate", &self.inner.mutation_rate)
+            .finish()
+    }
+}
+
+/// Returns the number of words holding `len` bits.
+fn words_for(len: usize) -> usize {
+    len.div_ceil(WORD_BITS)
+}
+
+/// Returns a mask of the bits of a word below `bit`.
+fn mask_below(bit: usize) -> u64 {
+    if bit >= WORD_BITS {
+        !0
+    } else {
+        (1 << bit) - 1
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// BitString is a unit of a `BitGenome`, being a fixed length string of bits
+/// packed into 64 bit words.
+#[derive(Clone)]
+pub struct BitString {
+    words: Vec<u64>,
+    len: usize,
+    genome: BitGenome,
+}
+
+impl BitString {
+    /// Returns the number of bits.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns false, as a unit always has at least one bit.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Returns the bit at `index`.
+    pub fn get(&self, index: usize) -> bool {
+        assert!(index < self.len);
+        self.words[index / WORD_BITS] >> (index % WORD_BITS) & 1 == 1
+    }
+
+    /// Sets the bit at `index`.
+    pub fn set(&mut self, index: usize, bit: bool) {
+        assert!(index < self.len);
+        let mask = 1 << (index % WORD_BITS);
+        if bit {
+            self.words[index / WORD_BITS] |= mask;
+        } else {
+            self.words[index / WORD_BITS] &= !mask;
+        }
+    }
+
+    /// Flips the bit at `index`.
+    pub fn flip(&mut self, index: usize) {
+        assert!(index < self.len);
+        self.words[index / WORD_BITS] ^= 1 << (index % WORD_BITS);
+    }
+
+    /// Returns the number of set bits.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    /// Returns an iterator over the bits in order.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(move |i| self.get(i))
+    }
+
+    /// Returns the indexes of the set bits in order.
+    pub fn ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..WORD_BITS)
+                .filter(move |bit| word >> bit & 1 == 1)
+                .map(move |bit| i * WORD_BITS + bit)
+        })
+    }
+
+    /// Returns the words holding the bits, where bit `i` is bit `i % 64` of
+    /// word `i / 64`, and the bits of the last word beyond the length are
+    /// clear.
+    pub fn words(&self) -> &[u64] {
+        &self.words
+    }
+
+    /// Clears the bits of the last word beyond the length.
+    fn clear_unused(&mut self) {
+        if let Some(last) = self.words.last_mut() {
+            *last &= mask_below(self.len - (self.len - 1) / WORD_BITS * WORD_BITS);
+        }
+    }
+
+    /// Copies the bits in `start..end` from another string of the same length.
+    fn copy_range(&mut self, other: &BitString, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+        let (first, last) = (start / WORD_BITS, (end - 1) / WORD_BITS);
+        for i in first..(last + 1) {
+            let low = if i == first { start % WORD_BITS } else { 0 };
+            let high = if i == last { end - last * WORD_BITS } else { WORD_BITS };
+            let mask = mask_below(high) & !mask_below(low);
+            self.words[i] = (self.words[i] & !mask) | (other.words[i] & mask);
+        }
+    }
+
+    /// Returns a child taking alternate segments from this string and another,
+    /// switching at each of the ascending `cuts`.
+    fn segments(&self, other: &BitString, cuts: &[usize]) -> BitString {
+        let mut child = self.clone();
+        let mut start = 0;
+        let mut from_other = false;
+        for end in cuts.iter().cloned().chain(Some(self.len)) {
+            if from_other {
+                child.copy_range(other, start, end);
+            }
+            start = end;
+            from_other = !from_other;
+        }
+        child
+    }
+
+    /// Flips each bit with probability `rate`, skipping between flips by
+    /// geometrically distributed gaps so that sparse mutation of long strings
+    /// is cheap.
+    fn mutate<R: Rng>(&mut self, rng: &mut R, rate: f64) {
+        if rate <= 0.0 {
+            return;
+        }
+        if rate >= 1.0 {
+            for word in &mut self.words {
+                *word = !*word;
+            }
+            self.clear_unused();
+            return;
+        }
+
+        let scale = 1.0 / (1.0 - rate).ln();
+        let mut index = 0;
+        loop {
+            let u: f64 = rng.gen();
+            let gap = ((1.0 - u).ln() * scale).floor();
+            if gap >= (self.len - index) as f64 {
+                return;
+            }
+            index += gap as usize;
+            self.flip(index);
+            index += 1;
+        }
+    }
+}
+
+impl PartialEq for BitString {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len && self.words == other.words
+    }
+}
+
+impl fmt::Debug for BitString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits: String = self.iter().map(|bit| if bit { '1' } else { '0' }).collect();
+        write!(f, "BitString({})", bits)
+    }
+}
+
+impl Unit for BitString {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.genome.inner.fitness)(self)
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let genome = &self.genome.inner;
+        let cuts = |rng: &mut R, n: usize| -> Vec<usize> {
+            // Cut points fall between bits, and so are within 1..len.
+            sample_sorted(rng, self.len - 1, n)
+                .into_iter()
+                .map(|cut| cut + 1)
+                .collect()
+        };
+
+        let mut child = match genome.crossover {
+            BitCrossover::OnePoint => {
+                let cuts = cuts(rng, 1);
+                self.segments(other, &cuts)
+            }
+            BitCrossover::TwoPoint => {
+                let cuts = cuts(rng, 2);
+                self.segments(other, &cuts)
+            }
+            BitCrossover::NPoint(n) => {
+                let cuts = cuts(rng, n);
+                self.segments(other, &cuts)
+            }
+            BitCrossover::Uniform => {
+                let mut child = self.clone();
+                for (word, &theirs) in child.words.iter_mut().zip(&other.words) {
+                    let mask: u64 = rng.gen();
+                    *word = (*word & mask) | (theirs & !mask);
+                }
+                child
+            }
+        };
+        child.mutate(rng, genome.mutation_rate);
+        child
+    }
+}
@@ -0,0 +1,423 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! A genome of named genes of differing types, for searching spaces such as
+//! hyperparameters that mix integers, real numbers and choices.
+
+use unit::Unit;
+use genome::FitnessFn;
+
+use rand::{self, Rng};
+use rand::distributions::{IndependentSample, Normal};
+
+use std::fmt;
+use std::sync::Arc;
+
+/// The type and range of a gene.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Gene {
+    /// An integer within the inclusive range `low..=high`.
+    Int {
+        /// The least value.
+        low: i64,
+        /// The greatest value.
+        high: i64,
+    },
+
+    /// A real number within the inclusive range `[low, high]`.
+    Float {
+        /// The least value.
+        low: f64,
+        /// The greatest value.
+        high: f64,
+    },
+
+    /// One of a number of named choices, which have no order.
+    Categorical {
+        /// The names of the choices.
+        choices: Vec<String>,
+    },
+
+    /// A flag that is either on or off.
+    Bool,
+}
+
+/// The value of a gene, being of the variant matching its `Gene`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    /// The value of an integer gene.
+    Int(i64),
+
+    /// The value of a real number gene.
+    Float(f64),
+
+    /// The index of the choice of a categorical gene.
+    Categorical(usize),
+
+    /// The value of a boolean gene.
+    Bool(bool),
+}
+
+/// How the genes of two parents are combined into a child.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MixedCrossover {
+    /// Draws each numeric gene uniformly from the interval spanned by the
+    /// parents, extended on both sides by `alpha` (>= 0) times its width and
+    /// rounded for integers, and takes every other gene from either parent
+    /// with equal probability. This is the default, with an `alpha` of 0.5.
+    Blend {
+        /// The extension of the interval.
+        alpha: f64,
+    },
+
+    /// Takes each gene from either parent with equal probability.
+    Uniform,
+}
+
+impl Default for MixedCrossover {
+    fn default() -> Self {
+        MixedCrossover::Blend { alpha: 0.5 }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// MixedGenome describes a schema of named genes of differing types, along
+/// with the fitness function and operators used to evolve it. Genes are added
+/// with `with_int`, `with_float`, `with_categorical` and `with_bool`, and units
+/// are created from it with `unit` or `random_units`, sharing its description.
+///
+/// Mutation is aware of the type of each gene: numeric genes take a normally
+/// distributed step, categorical genes switch to another choice and boolean
+/// genes are flipped.
+#[derive(Clone)]
+pub struct MixedGenome {
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone)]
+struct Inner {
+    names: Vec<String>,
+    genes: Vec<Gene>,
+    fitness: FitnessFn<Chromosome>,
+    crossover: MixedCrossover,
+    sigma: f64,
+    mutation_rate: Option<f64>,
+}
+
+impl MixedGenome {
+    /// Creates a genome without genes with the fitness function of a unit.
+    /// Units are bred with blend crossover, and one gene per child is mutated
+    /// on average, with numeric genes stepping by a tenth of their range.
+    pub fn new<F>(fitness: F) -> Self
+    where
+        F: Fn(&Chromosome) -> f64 + Send + Sync + 'static,
+    {
+        MixedGenome {
+            inner: Arc::new(Inner {
+                names: Vec::new(),
+                genes: Vec::new(),
+                fitness: Arc::new(fitness),
+                crossover: MixedCrossover::default(),
+                sigma: 0.1,
+                mutation_rate: None,
+            }),
+        }
+    }
+
+    /// Adds a gene, panicking if its name is already taken.
+    pub fn with_gene(mut self, name: &str, gene: Gene) -> Self {
+        match gene {
+            Gene::Int { low, high } => assert!(low <= high),
+            Gene::Float { low, high } => {
+                assert!(low.is_finite() && high.is_finite() && low <= high)
+            }
+            Gene::Categorical { ref choices } => assert!(!choices.is_empty()),
+            Gene::Bool => {}
+        }
+        assert!(self.index(name).is_none(), "gene {} already exists", name);
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.names.push(name.to_string());
+        inner.genes.push(gene);
+        self
+    }
+
+    /// Adds an integer gene within the inclusive range `low..=high`.
+    pub fn with_int(self, name: &str, low: i64, high: i64) -> Self {
+        self.with_gene(name, Gene::Int { low, high })
+    }
+
+    /// Adds a real number gene within the inclusive range `[low, high]`.
+    pub fn with_float(self, name: &str, low: f64, high: f64) -> Self {
+        self.with_gene(name, Gene::Float { low, high })
+    }
+
+    /// Adds a gene that takes one of a number (> 0) of named choices.
+    pub fn with_categorical(self, name: &str, choices: &[&str]) -> Self {
+        let choices = choices.iter().map(|c| c.to_string()).collect();
+        self.with_gene(name, Gene::Categorical { choices })
+    }
+
+    /// Adds a boolean gene.
+    pub fn with_bool(self, name: &str) -> Self {
+        self.with_gene(name, Gene::Bool)
+    }
+
+    /// Sets the crossover operator.
+    pub fn with_crossover(mut self, crossover: MixedCrossover) -> Self {
+        Arc::make_mut(&mut self.inner).crossover = crossover;
+        self
+    }
+
+    /// Sets the standard deviation (> 0) of the step taken by a mutated
+    /// numeric gene relative to the width of its range, and the probability
+    /// (0 <= r <= 1) that each gene of a child is mutated.
+    pub fn with_mutation(mut self, sigma: f64, rate: f64) -> Self {
+        assert!(sigma > 0.0);
+        assert!((0.0..=1.0).contains(&rate));
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.sigma = sigma;
+        inner.mutation_rate = Some(rate);
+        self
+    }
+
+    /// Returns the name and type of each gene in order.
+    pub fn genes(&self) -> impl Iterator<Item = (&str, &Gene)> {
+        self.inner.names.iter().map(|n| n.as_str()).zip(&self.inner.genes)
+    }
+
+    /// Creates a unit from the value of each gene in order, where numeric
+    /// values are clamped to their range. Panics if the values do not match
+    /// the genes.
+    pub fn unit(&self, mut values: Vec<Value>) -> Chromosome {
+        assert_eq!(values.len(), self.inner.genes.len(), "wrong number of values for the genome");
+        for (value, gene) in values.iter_mut().zip(&self.inner.genes) {
+            *value = match (*value, gene) {
+                (Value::Int(v), &Gene::Int { low, high }) => Value::Int(v.max(low).min(high)),
+                (Value::Float(v), &Gene::Float { low, high }) => Value::Float(v.max(low).min(high)),
+                (Value::Categorical(i), Gene::Categorical { choices }) => {
+                    assert!(i < choices.len(), "choice {} out of range", i);
+                    Value::Categorical(i)
+                }
+                (Value::Bool(b), &Gene::Bool) => Value::Bool(b),
+                (value, gene) => panic!("value {:?} does not match gene {:?}", value, gene),
+            };
+        }
+        Chromosome {
+            values,
+            genome: self.clone(),
+        }
+    }
+
+    /// Creates `n` units with the value of each gene drawn uniformly from its
+    /// range.
+    pub fn random_units<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<Chromosome> {
+        (0..n)
+            .map(|_| {
+                let values = self.inner.genes.iter().map(|gene| random_value(rng, gene)).collect();
+                self.unit(values)
+            })
+            .collect()
+    }
+
+    /// Returns the position of a gene by name.
+    fn index(&self, name: &str) -> Option<usize> {
+        self.inner.names.iter().position(|n| n == name)
+    }
+}
+
+impl fmt::Debug for MixedGenome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MixedGenome")
+            .field("genes", &self.genes().collect::<Vec<_>>())
+            .field("crossover", &self.inner.crossover)
+            .field("sigma", &self.inner.sigma)
+            .field("mutation_rate", &self.inner.mutation_rate)
+            .finish()
+    }
+}
+
+/// Returns a value drawn uniformly from `[low, high]`.
+fn uniform<R: Rng>(rng: &mut R, low: f64, high: f64) -> f64 {
+    low + (high - low) * rng.gen::<f64>()
+}
+
+/// Returns an integer drawn uniformly from `low..=high`.
+fn uniform_int<R: Rng>(rng: &mut R, low: i64, high: i64) -> i64 {
+    let value = uniform(rng, low as f64, high as f64 + 1.0).floor();
+    (value as i64).max(low).min(high)
+}
+
+/// Returns a value drawn uniformly from the range of a gene.
+fn random_value<R: Rng>(rng: &mut R, gene: &Gene) -> Value {
+    match *gene {
+        Gene::Int { low, high } => Value::Int(uniform_int(rng, low, high)),
+        Gene::Float { low, high } => Value::Float(uniform(rng, low, high)),
+        Gene::Categorical { ref choices } => Value::Categorical(rng.gen_range(0, choices.len())),
+        Gene::Bool => Value::Bool(rng.gen()),
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Chromosome is a unit of a `MixedGenome`, holding a value for each of its
+/// genes. Values are read by the name of their gene with the accessor matching
+/// its type, which panics if there is no such gene of that type.
+#[derive(Clone, Debug)]
+pub struct Chromosome {
+    values: Vec<Value>,
+    genome: MixedGenome,
+}
+
+impl Chromosome {
+    /// Returns the value of each gene in order.
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+
+    /// Returns the value of a gene by name.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.genome.index(name).map(|i| self.values[i])
+    }
+
+    /// Returns the value of an integer gene.
+    pub fn int(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(Value::Int(v)) => v,
+            _ => panic!("no integer gene named {}", name),
+        }
+    }
+
+    /// Returns the value of a real number gene.
+    pub fn float(&self, name: &str) -> f64 {
+        match self.get(name) {
+            Some(Value::Float(v)) => v,
+            _ => panic!("no real number gene named {}", name),
+        }
+    }
+
+    /// Returns the name of the choice of a categorical gene.
+    pub fn category(&self, name: &str) -> &str {
+        match self.genome.index(name).map(|i| (self.values[i], &self.genome.inner.genes[i])) {
+            Some((Value::Categorical(i), Gene::Categorical { choices })) => &choices[i],
+            _ => panic!("no categorical gene named {}", name),
+        }
+    }
+
+    /// Returns the value of a boolean gene.
+    pub fn bool(&self, name: &str) -> bool {
+        match self.get(name) {
+            Some(Value::Bool(v)) => v,
+            _ => panic!("no boolean gene named {}", name),
+        }
+    }
+
+    /// Consumes the unit, returning the value of each gene in order.
+    pub fn into_values(self) -> Vec<Value> {
+        self.values
+    }
+}
+
+impl PartialEq for Chromosome {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl Unit for Chromosome {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.genome.inner.fitness)(self)
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let genome = &self.genome.inner;
+        let mutation_rate = genome
+            .mutation_rate
+            .unwrap_or(1.0 / genome.genes.len().max(1) as f64);
+
+        let mut values = Vec::with_capacity(self.values.len());
+        for ((&a, &b), gene) in self.values.iter().zip(&other.values).zip(&genome.genes) {
+            let mut value = match (genome.crossover, a, b) {
+                (MixedCrossover::Blend { alpha }, Value::Int(a), Value::Int(b)) => {
+                    let (a, b) = (a as f64, b as f64);
+                    let extent = alpha * (a - b).abs();
+                    Value::Int(uniform(rng, a.min(b) - extent, a.max(b) + extent).round() as i64)
+                }
+                (MixedCrossover::Blend { alpha }, Value::Float(a), Value::Float(b)) => {
+                    let extent = alpha * (a - b).abs();
+                    Value::Float(uniform(rng, a.min(b) - extent, a.max(b) + extent))
+                }
+                _ => if rng.gen() { a } else { b },
+            };
+            if rng.gen::<f64>() < mutation_rate {
+                value = mutate(rng, value, gene, genome.sigma);
+            }
+            values.push(value);
+        }
+
+        self.genome.unit(values)
+    }
+}
+
+/// Returns a value mutated according to the type of its gene.
+fn mutate<R: Rng>(rng: &mut R, value: Value, gene: &Gene, sigma: f64) -> Value {
+    match (value, gene) {
+        (Value::Int(v), &Gene::Int { low, high }) => {
+            if low == high {
+                return value;
+            }
+            // Always take a step, so that narrow ranges still mutate.
+            let normal = Normal::new(0.0, sigma * (high as f64 - low as f64));
+            let step = normal.ind_sample(rng).round() as i64;
+            let step = if step != 0 {
+                step
+            } else if v == high || (v != low && rng.gen()) {
+                -1
+            } else {
+                1
+            };
+            Value::Int(v.saturating_add(step))
+        }
+        (Value::Float(v), &Gene::Float { low, high }) => {
+            if low == high {
+                return value;
+            }
+            let normal = Normal::new(0.0, sigma * (high - low));
+            Value::Float(v + normal.ind_sample(rng))
+        }
+        (Value::Categorical(i), Gene::Categorical { choices }) => {
+            if choices.len() < 2 {
+                return value;
+            }
+            // Draw from the other choices.
+            let j = rng.gen_range(0, choices.len() - 1);
+            Value::Categorical(if j >= i { j + 1 } else { j })
+        }
+        (Value::Bool(b), &Gene::Bool) => Value::Bool(!b),
+        _ => value,
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! Ready-made genomes that implement `Unit` from a fitness closure, along with
+//! the standard crossover and mutation operators for each.
+//!
+//! Each genome is configured once by a shared description of its shape and
+//! operators, from which units are created. Breeding draws all randomness from
+//! the generator given by the population, and so runs are reproducible for a
+//! given seed.
+
+use rand::Rng;
+use rand::distributions::{IndependentSample, Range};
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+pub mod bit_string;
+pub mod mixed;
+pub mod permutation;
+pub mod real_vector;
+
+/// The fitness function of a genome, shared between all of its units.
+type FitnessFn<G> = Arc<dyn Fn(&G) -> f64 + Send + Sync>;
+
+/// Returns `amount` distinct indexes drawn uniformly from `0..length` in
+/// ascending order, using Floyd's algorithm so that the cost depends only on
+/// the amount drawn.
+fn sample_sorted<R: Rng>(rng: &mut R, length: usize, amount: usize) -> Vec<usize> {
+    let amount = amount.min(length);
+    let mut chosen = BTreeSet::new();
+    for j in (length - amount)..length {
+        let t = Range::new(0, j + 1).ind_sample(rng);
+        if !chosen.insert(t) {
+            chosen.insert(j);
+        }
+    }
+    chosen.into_iter().collect()
+}
@@ -0,0 +1,382 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! A genome of orderings of `0..n`, for routing, scheduling and other problems
+//! where a solution is a sequence of distinct items.
+
+use unit::Unit;
+use genome::{sample_sorted, FitnessFn};
+
+use rand::{self, Rng};
+
+use std::fmt;
+use std::sync::Arc;
+
+/// How the orders of two parents are combined into a child. Every operator
+/// produces a valid permutation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PermutationCrossover {
+    /// Order crossover (OX), which copies a random segment of one parent into
+    /// the child and fills the remaining positions with the missing items in
+    /// the order they follow the segment in the other parent. This is the
+    /// default.
+    #[default]
+    Order,
+
+    /// Partially mapped crossover (PMX), which copies a random segment of one
+    /// parent and takes the remaining positions from the other parent, mapping
+    /// any item already in the segment through the segment's correspondence
+    /// between the parents.
+    PartiallyMapped,
+
+    /// Cycle crossover (CX), which keeps every item at the position it has in
+    /// one of the parents, taking alternate cycles of positions from each.
+    Cycle,
+
+    /// Edge recombination, which builds the child from the adjacencies of both
+    /// parents, treating each order as a closed tour, and so suits problems
+    /// where the neighbours of an item matter more than its position.
+    EdgeRecombination,
+}
+
+/// How the order of a child is mutated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PermutationMutation {
+    /// Swaps the items at two random positions.
+    Swap,
+
+    /// Moves an item from a random position to another.
+    Insert,
+
+    /// Reverses the order of a random segment. This is the default.
+    #[default]
+    Inversion,
+
+    /// Shuffles the items of a random segment.
+    Scramble,
+}
+
+//------------------------------------------------------------------------------
+
+/// PermutationGenome describes an ordering of the items `0..n`, along with the
+/// fitness function and operators used to evolve it. Units are created from it
+/// with `unit`, `identity` or `random_units`, and share its description.
+#[derive(Clone)]
+pub struct PermutationGenome {
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone)]
+struct Inner {
+    len: usize,
+    fitness: FitnessFn<[usize]>,
+    crossover: PermutationCrossover,
+    mutation: PermutationMutation,
+    mutation_rate: f64,
+}
+
+impl PermutationGenome {
+    /// Creates a genome ordering `len` (> 0) items with the fitness function of
+    /// an order. Units are bred with order crossover, and half of all children
+    /// have a segment inverted.
+    pub fn new<F>(len: usize, fitness: F) -> Self
+    where
+        F: Fn(&[usize]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(len > 0);
+        PermutationGenome {
+            inner: Arc::new(Inner {
+                len,
+                fitness: Arc::new(fitness),
+                crossover: PermutationCrossover::default(),
+                mutation: PermutationMutation::default(),
+                mutation_rate: 0.5,
+            }),
+        }
+    }
+
+    /// Sets the crossover operator.
+    pub fn with_crossover(mut self, crossover: PermutationCrossover) -> Self {
+        Arc::make_mut(&mut self.inner).crossover = crossover;
+        self
+    }
+
+    /// Sets the mutation operator, and the probability (0 <= r <= 1) that a
+    /// child is mutated.
+    pub fn with_mutation(mut self, mutation: PermutationMutation, rate: f64) -> Self {
+        assert!((0.0..=1.0).contains(&rate));
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.mutation = mutation;
+        inner.mutation_rate = rate;
+        self
+    }
+
+    /// Returns the number of items ordered by each unit.
+    pub fn len(&self) -> usize {
+        self.inner.len
+    }
+
+    /// Returns false, as a genome always orders at least one item.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Creates a unit with the items in ascending order.
+    pub fn identity(&self) -> Permutation {
+        Permutation {
+            order: (0..self.inner.len).collect(),
+            genome: self.clone(),
+        }
+    }
+
+    /// Creates a unit from an order. Panics if the order is not a permutation
+    /// of the items of the genome.
+    pub fn unit(&self, order: Vec<usize>) -> Permutation {
+        assert_eq!(order.len(), self.inner.len, "wrong number of items for the genome");
+        let mut seen = vec![false; order.len()];
+        for &item in &order {
+            assert!(item < seen.len() && !seen[item], "order is not a permutation");
+            seen[item] = true;
+        }
+        Permutation {
+            order,
+            genome: self.clone(),
+        }
+    }
+
+    /// Creates `n` units with orders drawn uniformly at random.
+    pub fn random_units<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<Permutation> {
+        (0..n)
+            .map(|_| {
+                let mut unit = self.identity();
+                rng.shuffle(&mut unit.order);
+                unit
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for PermutationGenome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PermutationGenome")
+            .field("len", &self.inner.len)
+            .field("crossover", &self.inner.crossover)
+            .field("mutation", &self.inner.mutation)
+            .field("mutation_rate", &self.inner.mutation_rate)
+            .finish()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Permutation is a unit of a `PermutationGenome`, being an ordering of the
+/// items `0..n` where each item appears exactly once.
+#[derive(Clone, Debug)]
+pub struct Permutation {
+    order: Vec<usize>,
+    genome: PermutationGenome,
+}
+
+impl Permutation {
+    /// Returns the items in order.
+    pub fn order(&self) -> &[usize] {
+        &self.order
+    }
+
+    /// Consumes the unit, returning its order.
+    pub fn into_order(self) -> Vec<usize> {
+        self.order
+    }
+}
+
+impl PartialEq for Permutation {
+    fn eq(&self, other: &Self) -> bool {
+        self.order == other.order
+    }
+}
+
+impl Unit for Permutation {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.genome.inner.fitness)(&self.order)
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let genome = &self.genome.inner;
+        let (a, b) = (&self.order, &other.order);
+        let mut order = match genome.crossover {
+            PermutationCrossover::Order => order_crossover(rng, a, b),
+            PermutationCrossover::PartiallyMapped => partially_mapped(rng, a, b),
+            PermutationCrossover::Cycle => cycle_crossover(a, b),
+            PermutationCrossover::EdgeRecombination => edge_recombination(rng, a, b),
+        };
+
+        if order.len() > 1 && rng.gen::<f64>() < genome.mutation_rate {
+            let (i, j) = (rng.gen_range(0, order.len()), rng.gen_range(0, order.len()));
+            let (low, high) = (i.min(j), i.max(j));
+            match genome.mutation {
+                PermutationMutation::Swap => order.swap(i, j),
+                PermutationMutation::Insert => {
+                    let item = order.remove(i);
+                    order.insert(j, item);
+                }
+                PermutationMutation::Inversion => order[low..high + 1].reverse(),
+                PermutationMutation::Scramble => rng.shuffle(&mut order[low..high + 1]),
+            }
+        }
+
+        Permutation {
+            order,
+            genome: self.genome.clone(),
+        }
+    }
+}
+
+/// Returns the position of each item in an order.
+fn positions(order: &[usize]) -> Vec<usize> {
+    let mut positions = vec![0; order.len()];
+    for (i, &item) in order.iter().enumerate() {
+        positions[item] = i;
+    }
+    positions
+}
+
+/// Returns the bounds of a random non-empty segment of an order of `len`
+/// items.
+fn segment<R: Rng>(rng: &mut R, len: usize) -> (usize, usize) {
+    let cuts = sample_sorted(rng, len + 1, 2);
+    (cuts[0], cuts[1])
+}
+
+fn order_crossover<R: Rng>(rng: &mut R, a: &[usize], b: &[usize]) -> Vec<usize> {
+    let len = a.len();
+    let (start, end) = segment(rng, len);
+    let mut child = a.to_vec();
+    let mut taken = vec![false; len];
+    for &item in &a[start..end] {
+        taken[item] = true;
+    }
+
+    let mut free = (end..len).chain(0..start);
+    for &item in b[end..].iter().chain(&b[..end]) {
+        if !taken[item] {
+            child[free.next().unwrap()] = item;
+        }
+    }
+    child
+}
+
+fn partially_mapped<R: Rng>(rng: &mut R, a: &[usize], b: &[usize]) -> Vec<usize> {
+    let len = a.len();
+    let (start, end) = segment(rng, len);
+    let a_positions = positions(a);
+    let mut in_segment = vec![false; len];
+    for &item in &a[start..end] {
+        in_segment[item] = true;
+    }
+
+    let mut child = a.to_vec();
+    for i in (0..start).chain(end..len) {
+        let mut item = b[i];
+        while in_segment[item] {
+            item = b[a_positions[item]];
+        }
+        child[i] = item;
+    }
+    child
+}
+
+fn cycle_crossover(a: &[usize], b: &[usize]) -> Vec<usize> {
+    let a_positions = positions(a);
+    let mut child = a.to_vec();
+    let mut visited = vec![false; a.len()];
+    let mut from_b = false;
+    for start in 0..a.len() {
+        if visited[start] {
+            continue;
+        }
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            if from_b {
+                child[i] = b[i];
+            }
+            i = a_positions[b[i]];
+        }
+        from_b = !from_b;
+    }
+    child
+}
+
+fn edge_recombination<R: Rng>(rng: &mut R, a: &[usize], b: &[usize]) -> Vec<usize> {
+    let len = a.len();
+    let mut edges: Vec<Vec<usize>> = vec![Vec::with_capacity(4); len];
+    for order in &[a, b] {
+        for i in 0..len {
+            let item = order[i];
+            for &neighbour in &[order[(i + len - 1) % len], order[(i + 1) % len]] {
+                if neighbour != item && !edges[item].contains(&neighbour) {
+                    edges[item].push(neighbour);
+                }
+            }
+        }
+    }
+
+    // Unvisited items, with the index of each within it for constant time
+    // removal.
+    let mut remaining: Vec<usize> = (0..len).collect();
+    let mut index: Vec<usize> = (0..len).collect();
+
+    let mut child = Vec::with_capacity(len);
+    let mut current = if rng.gen() { a[0] } else { b[0] };
+    loop {
+        child.push(current);
+        let last = *remaining.last().unwrap();
+        remaining.swap_remove(index[current]);
+        index[last] = index[current];
+        if remaining.is_empty() {
+            return child;
+        }
+
+        let neighbours = ::std::mem::take(&mut edges[current]);
+        for &neighbour in &neighbours {
+            edges[neighbour].retain(|&e| e != current);
+        }
+
+        // Prefer the neighbour with the fewest edges of its own, breaking ties
+        // at random, and fall back to a random unvisited item.
+        let fewest = neighbours.iter().map(|&n| edges[n].len()).min();
+        let candidates: Vec<usize> = neighbours
+            .into_iter()
+            .filter(|&n| Some(edges[n].len()) == fewest)
+            .collect();
+        current = if candidates.is_empty() {
+            remaining[rng.gen_range(0, remaining.len())]
+        } else {
+            candidates[rng.gen_range(0, candidates.len())]
+        };
+    }
+}
@@ -0,0 +1,336 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! A genome of real numbers, each within its own bounds.
+
+use unit::Unit;
+use genome::FitnessFn;
+
+use rand::{self, Rng};
+use rand::distributions::{IndependentSample, Normal};
+
+use std::fmt;
+use std::sync::Arc;
+
+/// How the values of two parents are combined into a child.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RealCrossover {
+    /// Simulated binary crossover, which places each value of the child near
+    /// one of the parents with a spread like that of one-point crossover on a
+    /// binary encoding. A greater distribution index `eta` (>= 0) keeps the
+    /// child closer to its parents. This is the default, with an `eta` of 15.
+    Sbx {
+        /// The distribution index.
+        eta: f64,
+    },
+
+    /// Blend crossover, which draws each value uniformly from the interval
+    /// spanned by the parents, extended on both sides by `alpha` (>= 0) times
+    /// its width.
+    BlxAlpha {
+        /// The extension of the interval.
+        alpha: f64,
+    },
+
+    /// Arithmetic crossover, which takes a weighted mean of the parents with a
+    /// weight drawn at random for each child.
+    Arithmetic,
+
+    /// Uniform crossover, which takes each value from either parent with equal
+    /// probability.
+    Uniform,
+}
+
+impl Default for RealCrossover {
+    fn default() -> Self {
+        RealCrossover::Sbx { eta: 15.0 }
+    }
+}
+
+/// How a value of a child is mutated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RealMutation {
+    /// Adds normally distributed noise, with a standard deviation of `sigma`
+    /// (> 0) times the width of the bounds of the value.
+    Gaussian {
+        /// The standard deviation relative to the width of the bounds.
+        sigma: f64,
+    },
+
+    /// Polynomial mutation, which perturbs the value by a polynomial
+    /// distribution that reaches exactly to its bounds. A greater distribution
+    /// index `eta` (>= 0) makes smaller perturbations more likely. This is the
+    /// default, with an `eta` of 20.
+    Polynomial {
+        /// The distribution index.
+        eta: f64,
+    },
+
+    /// Replaces the value with one drawn uniformly from its bounds.
+    Uniform,
+}
+
+impl Default for RealMutation {
+    fn default() -> Self {
+        RealMutation::Polynomial { eta: 20.0 }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// RealGenome describes a vector of real numbers, each within its own bounds,
+/// along with the fitness function and operators used to evolve it. Units are
+/// created from it with `unit` or `random_units`, and share its description.
+#[derive(Clone)]
+pub struct RealGenome {
+    inner: Arc<Inner>,
+}
+
+#[derive(Clone)]
+struct Inner {
+    bounds: Vec<(f64, f64)>,
+    fitness: FitnessFn<[f64]>,
+    crossover: RealCrossover,
+    mutation: RealMutation,
+    mutation_rate: f64,
+}
+
+impl RealGenome {
+    /// Creates a genome with a value for each of `bounds`, each being the
+    /// inclusive range `(low, high)` of that value, and the fitness function
+    /// of the values. Units are bred with simulated binary crossover and
+    /// polynomial mutation of one value per child on average.
+    pub fn new<F>(bounds: Vec<(f64, f64)>, fitness: F) -> Self
+    where
+        F: Fn(&[f64]) -> f64 + Send + Sync + 'static,
+    {
+        assert!(!bounds.is_empty());
+        for &(low, high) in &bounds {
+            assert!(low.is_finite() && high.is_finite() && low <= high);
+        }
+        let mutation_rate = 1.0 / bounds.len() as f64;
+        RealGenome {
+            inner: Arc::new(Inner {
+                bounds,
+                fitness: Arc::new(fitness),
+                crossover: RealCrossover::default(),
+                mutation: RealMutation::default(),
+                mutation_rate,
+            }),
+        }
+    }
+
+    /// Sets the crossover operator.
+    pub fn with_crossover(mut self, crossover: RealCrossover) -> Self {
+        match crossover {
+            RealCrossover::Sbx { eta } => assert!(eta >= 0.0),
+            RealCrossover::BlxAlpha { alpha } => assert!(alpha >= 0.0),
+            RealCrossover::Arithmetic | RealCrossover::Uniform => {}
+        }
+        Arc::make_mut(&mut self.inner).crossover = crossover;
+        self
+    }
+
+    /// Sets the mutation operator, and the probability (0 <= r <= 1) that
+    /// each value of a child is mutated.
+    pub fn with_mutation(mut self, mutation: RealMutation, rate: f64) -> Self {
+        match mutation {
+            RealMutation::Gaussian { sigma } => assert!(sigma > 0.0),
+            RealMutation::Polynomial { eta } => assert!(eta >= 0.0),
+            RealMutation::Uniform => {}
+        }
+        assert!((0.0..=1.0).contains(&rate));
+        let inner = Arc::make_mut(&mut self.inner);
+        inner.mutation = mutation;
+        inner.mutation_rate = rate;
+        self
+    }
+
+    /// Returns the bounds of each value.
+    pub fn bounds(&self) -> &[(f64, f64)] {
+        &self.inner.bounds
+    }
+
+    /// Creates a unit from its values, which are clamped to their bounds.
+    /// Panics if the number of values does not match the genome.
+    pub fn unit(&self, mut values: Vec<f64>) -> RealVector {
+        assert_eq!(values.len(), self.inner.bounds.len());
+        for (value, &(low, high)) in values.iter_mut().zip(&self.inner.bounds) {
+            *value = value.max(low).min(high);
+        }
+        RealVector {
+            values,
+            genome: self.clone(),
+        }
+    }
+
+    /// Creates `n` units with values drawn uniformly from their bounds.
+    pub fn random_units<R: Rng>(&self, n: usize, rng: &mut R) -> Vec<RealVector> {
+        (0..n)
+            .map(|_| {
+                let values = self.inner
+                    .bounds
+                    .iter()
+                    .map(|&(low, high)| uniform(rng, low, high))
+                    .collect();
+                self.unit(values)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for RealGenome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RealGenome")
+            .field("bounds", &self.inner.bounds)
+            .field("crossover", &self.inner.crossover)
+            .field("mutation", &self.inner.mutation)
+            .field("mutation_rate", &self.inner.mutation_rate)
+            .finish()
+    }
+}
+
+/// Returns a value drawn uniformly from `[low, high]`.
+fn uniform<R: Rng>(rng: &mut R, low: f64, high: f64) -> f64 {
+    low + (high - low) * rng.gen::<f64>()
+}
+
+//------------------------------------------------------------------------------
+
+/// RealVector is a unit of a `RealGenome`, being a vector of real numbers
+/// within their bounds.
+#[derive(Clone, Debug)]
+pub struct RealVector {
+    values: Vec<f64>,
+    genome: RealGenome,
+}
+
+impl RealVector {
+    /// Returns the values of the unit.
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Consumes the unit, returning its values.
+    pub fn into_values(self) -> Vec<f64> {
+        self.values
+    }
+}
+
+impl PartialEq for RealVector {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl Unit for RealVector {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.genome.inner.fitness)(&self.values)
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let genome = &self.genome.inner;
+        let mut values: Vec<f64> = match genome.crossover {
+            RealCrossover::Sbx { eta } => self.values
+                .iter()
+                .zip(&other.values)
+                .map(|(&a, &b)| sbx(rng, a, b, eta))
+                .collect(),
+            RealCrossover::BlxAlpha { alpha } => self.values
+                .iter()
+                .zip(&other.values)
+                .map(|(&a, &b)| {
+                    let extent = alpha * (a - b).abs();
+                    uniform(rng, a.min(b) - extent, a.max(b) + extent)
+                })
+                .collect(),
+            RealCrossover::Arithmetic => {
+                let weight: f64 = rng.gen();
+                self.values
+                    .iter()
+                    .zip(&other.values)
+                    .map(|(&a, &b)| weight * a + (1.0 - weight) * b)
+                    .collect()
+            }
+            RealCrossover::Uniform => self.values
+                .iter()
+                .zip(&other.values)
+                .map(|(&a, &b)| if rng.gen() { a } else { b })
+                .collect(),
+        };
+
+        for (value, &(low, high)) in values.iter_mut().zip(&genome.bounds) {
+            if rng.gen::<f64>() < genome.mutation_rate {
+                *value = match genome.mutation {
+                    RealMutation::Gaussian { sigma } => {
+                        let normal = Normal::new(0.0, sigma * (high - low));
+                        *value + normal.ind_sample(rng)
+                    }
+                    RealMutation::Polynomial { eta } => polynomial(rng, *value, low, high, eta),
+                    RealMutation::Uniform => uniform(rng, low, high),
+                };
+            }
+            *value = value.max(low).min(high);
+        }
+
+        RealVector {
+            values,
+            genome: self.genome.clone(),
+        }
+    }
+}
+
+/// Returns a value bred from `a` and `b` by simulated binary crossover, being
+/// either of the pair of children with equal probability.
+fn sbx<R: Rng>(rng: &mut R, a: f64, b: f64, eta: f64) -> f64 {
+    let u: f64 = rng.gen();
+    let beta = if u <= 0.5 {
+        (2.0 * u).powf(1.0 / (eta + 1.0))
+    } else {
+        (1.0 / (2.0 * (1.0 - u))).powf(1.0 / (eta + 1.0))
+    };
+    let sign = if rng.gen() { 1.0 } else { -1.0 };
+    0.5 * ((a + b) + sign * beta * (a - b))
+}
+
+/// Returns `value` perturbed by polynomial mutation within `[low, high]`.
+fn polynomial<R: Rng>(rng: &mut R, value: f64, low: f64, high: f64, eta: f64) -> f64 {
+    let width = high - low;
+    if width <= 0.0 {
+        return value;
+    }
+    let u: f64 = rng.gen();
+    let power = 1.0 / (eta + 1.0);
+    let delta = if u < 0.5 {
+        let from_low = 1.0 - (value - low) / width;
+        (2.0 * u + (1.0 - 2.0 * u) * from_low.powf(eta + 1.0)).powf(power) - 1.0
+    } else {
+        let from_high = 1.0 - (high - value) / width;
+        1.0 - (2.0 * (1.0 - u) + 2.0 * (u - 0.5) * from_high.powf(eta + 1.0)).powf(power)
+    };
+    value + delta * width
+}
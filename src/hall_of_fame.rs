@@ -0,0 +1,186 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use unit::Unit;
+use population::LazyUnit;
+use report::GenerationStats;
+use termination::stagnated;
+use fitness::Objective;
+
+/// A unit kept by a `HallOfFame`.
+#[derive(Clone, Debug)]
+pub struct Entry<T: Unit> {
+    /// The unit.
+    pub unit: T,
+
+    /// The fitness of the unit.
+    pub fitness: T::Fitness,
+
+    /// The generation in which the unit first appeared.
+    pub generation: u32,
+}
+
+/// HallOfFame is an archive of the strongest distinct units ever evaluated by a
+/// population, which survives even when those units are lost from the
+/// population itself.
+///
+/// Optionally the archive can be re-injected into the population when the best
+/// fitness stagnates, replacing the weakest units of the current generation.
+pub struct HallOfFame<T: Unit> {
+    capacity: usize,
+    reinject_after: Option<usize>,
+    last_reinjected: usize,
+    entries: Vec<Entry<T>>,
+
+    // A population accepts any unit, so the extra bounds the archive needs are
+    // captured as function pointers by `new`, where `T: Clone + PartialEq` is
+    // known. This keeps those bounds off `Population` and its methods. Other
+    // optional parts of a population that need more of `T` do the same.
+    eq: fn(&T, &T) -> bool,
+    clone: fn(&T) -> T,
+}
+
+impl<T: Unit + Clone + PartialEq> HallOfFame<T> {
+    /// Creates an archive of the `capacity` (> 0) strongest distinct units.
+    /// Units are distinct when they are not equal.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0);
+        HallOfFame {
+            capacity,
+            reinject_after: None,
+            last_reinjected: 0,
+            entries: Vec::new(),
+            eq: T::eq,
+            clone: T::clone,
+        }
+    }
+}
+
+impl<T: Unit> HallOfFame<T> {
+    /// Re-injects the archived units into the population whenever the best
+    /// fitness has not improved for `generations` (> 0) generations. After a
+    /// re-injection the population is given another `generations` to improve
+    /// before the next.
+    pub fn reinject_after(mut self, generations: usize) -> Self {
+        assert!(generations > 0);
+        self.reinject_after = Some(generations);
+        self
+    }
+
+    /// Returns the archived units ordered such that the first element is the
+    /// strongest.
+    pub fn entries(&self) -> &[Entry<T>] {
+        &self.entries
+    }
+
+    /// Returns the strongest unit ever evaluated.
+    pub fn best(&self) -> Option<&Entry<T>> {
+        self.entries.first()
+    }
+
+    /// Returns the number of archived units.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no units have been archived.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Consumes the archive, returning its entries.
+    pub fn into_entries(self) -> Vec<Entry<T>> {
+        self.entries
+    }
+}
+
+impl<T: Unit> HallOfFame<T> {
+    /// Records the units of an evaluated generation, sorted from strongest to
+    /// weakest.
+    pub(crate) fn record(&mut self, units: &[LazyUnit<T>], generation: u32, objective: Objective) {
+        for unit in units {
+            let fitness = match unit.lazy_fitness {
+                Some(ref fitness) => fitness,
+                None => continue,
+            };
+            let full = self.entries.len() == self.capacity;
+            if full && !objective.is_better(fitness, &self.entries[self.capacity - 1].fitness) {
+                // Units are sorted, so none of the remaining units qualify.
+                break;
+            }
+            if self.entries.iter().any(|e| (self.eq)(&e.unit, &unit.unit)) {
+                continue;
+            }
+
+            let index = self.entries
+                .iter()
+                .position(|e| objective.is_better(fitness, &e.fitness))
+                .unwrap_or(self.entries.len());
+            self.entries.insert(
+                index,
+                Entry {
+                    unit: (self.clone)(&unit.unit),
+                    fitness: fitness.clone(),
+                    generation,
+                },
+            );
+            self.entries.truncate(self.capacity);
+        }
+    }
+
+    /// Replaces the weakest units of an evaluated generation, sorted from
+    /// strongest to weakest, with archived units that are not already present
+    /// if the best fitness has stagnated. Returns true if units were replaced.
+    pub(crate) fn reinject(
+        &mut self,
+        units: &mut Vec<LazyUnit<T>>,
+        history: &[GenerationStats<T::Fitness>],
+        objective: Objective,
+    ) -> bool {
+        let generations = match self.reinject_after {
+            Some(g) => g,
+            None => return false,
+        };
+        if history.len() < self.last_reinjected + generations || !stagnated(history, generations, objective) {
+            return false;
+        }
+        self.last_reinjected = history.len();
+
+        let n_units = units.len();
+        let missing: Vec<LazyUnit<T>> = self.entries
+            .iter()
+            .filter(|e| !units.iter().any(|u| (self.eq)(&e.unit, &u.unit)))
+            .take(n_units)
+            .map(|e| {
+                LazyUnit {
+                    unit: (self.clone)(&e.unit),
+                    lazy_fitness: Some(e.fitness.clone()),
+                }
+            })
+            .collect();
+        if missing.is_empty() {
+            return false;
+        }
+
+        units.truncate(n_units - missing.len());
+        units.extend(missing);
+        true
+    }
+}
@@ -30,8 +30,9 @@
 //! extern crate spiril;
 //! extern crate rand;
 //!
-//! use spiril::unit::Unit;
+//! use spiril::unit::ScalarUnit;
 //! use spiril::population::Population;
+//! use spiril::termination::TargetFitness;
 //! use rand::{StdRng, SeedableRng, Rng};
 //!
 //! struct SudokuUnit {
@@ -39,7 +40,7 @@
 //!     answer: Vec<usize>, // 9x9 grid
 //! }
 //!
-//! impl Unit for SudokuUnit {
+//! impl ScalarUnit for SudokuUnit {
 //!     fn fitness(&self) -> f64 {
 //!         let mut score = 1.0_f64;
 //!
@@ -71,6 +72,10 @@
 //!     }
 //!
 //!     fn breed_with(&self, other: &SudokuUnit) -> SudokuUnit {
+//!         self.breed_with_rng(other, &mut rand::thread_rng())
+//!     }
+//!
+//!     fn breed_with_rng<R: Rng>(&self, other: &SudokuUnit, rng: &mut R) -> SudokuUnit {
 //!         // Even rows taken from self, odd rows taken from other.
 //!         // Mutations applied at random.
 //!         let mut new_unit: SudokuUnit = SudokuUnit {
@@ -81,7 +86,7 @@
 //!         (0_usize..81_usize)
 //!             .filter(|x| self.sudoku[*x] == 0)
 //!             .map(|x| {
-//!                 if rand::thread_rng().gen_range(0, 1) == 1 {
+//!                 if rng.gen_range(0, 1) == 1 {
 //!                     new_unit.answer[x] = other.answer[x];
 //!                 }
 //!                 new_unit.answer[x]
@@ -89,9 +94,9 @@
 //!             .last();
 //!
 //!         loop {
-//!             let i = rand::thread_rng().gen_range(0, 81);
+//!             let i = rng.gen_range(0, 81);
 //!             if self.sudoku[i] == 0 {
-//!                 new_unit.answer[i] = rand::thread_rng().gen_range(1, 10);
+//!                 new_unit.answer[i] = rng.gen_range(1, 10);
 //!                 break;
 //!             }
 //!         }
@@ -138,18 +143,35 @@
 //!         .set_size(1000)
 //!         .set_breed_factor(0.3)
 //!         .set_survival_factor(1.0)
+//!         .set_termination(TargetFitness::new(1.0))
 //!         .epochs(5000)
-//!         .finish()
-//!         .first()
-//!         .unwrap()
-//!         .fitness(), 1.0);
+//!         .best_fitness(), Some(1.0));
 //! }
 //! ```
 
-extern crate crossbeam;
 extern crate rand;
+#[cfg(feature = "serde")]
+extern crate bincode;
+#[cfg(feature = "serde")]
+extern crate serde;
+#[cfg(feature = "serde")]
+#[macro_use]
+extern crate serde_derive;
 
+#[cfg(test)]
 mod test;
 
+#[cfg(feature = "serde")]
+pub mod checkpoint;
+pub mod evaluator;
+pub mod fitness;
+pub mod genome;
+pub mod hall_of_fame;
+pub mod multi_objective;
+pub mod observer;
+pub mod pareto;
 pub mod population;
+pub mod report;
+pub mod selection;
+pub mod termination;
 pub mod unit;
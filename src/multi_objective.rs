@@ -0,0 +1,967 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! Optimising units against several objectives at once, where rather than a
+//! single strongest unit the result is a Pareto front of units that each trade
+//! one objective against another.
+
+use unit::Unit;
+use population::{LazyUnit, PopulationError};
+use fitness::Objective;
+use pareto::{self, ParetoArchive};
+use evaluator::{self, EvaluationError, Failure, FailurePolicy, ThreadPool};
+
+use rand::{Rng, SeedableRng, StdRng, XorShiftRng};
+use rand::distributions::{IndependentSample, Range};
+
+use std::cmp::Ordering;
+use std::error::Error;
+use std::f64;
+use std::mem;
+
+/// MultiObjectiveUnit is a unit that is measured by a number of objectives
+/// rather than a single fitness, such as the cost, latency and reliability of
+/// a design.
+pub trait MultiObjectiveUnit: Send + Sized {
+    /// Calculate the value of each objective of this unit. Every unit of a
+    /// population must return the same number of objectives in the same order.
+    fn objectives(&self) -> Vec<f64>;
+
+    /// Calculate the value of each objective in the same way as `objectives`,
+    /// but allowing the calculation to fail. Failures, along with panics and
+    /// objectives of NaN, are handled according to the `FailurePolicy` of the
+    /// population.
+    ///
+    /// By default this calls `objectives`.
+    fn try_objectives(&self) -> Result<Vec<f64>, Box<dyn Error + Send + Sync>> {
+        Ok(self.objectives())
+    }
+
+    /// Create a new unit by merging variable qualities from this and one other
+    /// unit, in the same way as `Unit::breed_with`.
+    fn breed_with(&self, other: &Self) -> Self;
+
+    /// Create a new unit in the same way as `breed_with`, but drawing all
+    /// randomness from `rng`, in the same way as `Unit::breed_with_rng`.
+    ///
+    /// By default this ignores `rng` and calls `breed_with`.
+    fn breed_with_rng<R: Rng>(&self, other: &Self, _rng: &mut R) -> Self {
+        self.breed_with(other)
+    }
+}
+
+/// Adapts a multi-objective unit into a unit whose fitness is its vector of
+/// objectives, so that it is evaluated in the same way as any other unit.
+pub(crate) struct Objectives<T>(pub(crate) T);
+
+impl<T: MultiObjectiveUnit> Unit for Objectives<T> {
+    type Fitness = Vec<f64>;
+
+    fn fitness(&self) -> Vec<f64> {
+        self.0.objectives()
+    }
+
+    fn try_fitness(&self) -> Result<Vec<f64>, Box<dyn Error + Send + Sync>> {
+        self.0.try_objectives()
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        Objectives(self.0.breed_with(&other.0))
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        Objectives(self.0.breed_with_rng(&other.0, rng))
+    }
+}
+
+/// A unit of a multi-objective population along with its objectives, as
+/// returned by `MultiObjectivePopulation::finish`.
+#[derive(Clone, Debug)]
+pub struct Solution<T> {
+    /// The unit.
+    pub unit: T,
+
+    /// The value of each objective of the unit.
+    pub objectives: Vec<f64>,
+
+    /// The index of the non-dominated front the unit belongs to, where 0 is
+    /// the Pareto front of the population.
+    pub rank: usize,
+}
+
+//------------------------------------------------------------------------------
+
+/// The algorithm with which a `MultiObjectivePopulation` chooses parents and
+/// decides which units survive each epoch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+    /// NSGA-II, where parents are chosen by binary tournament and the next
+    /// generation is chosen from the parents and children combined by fast
+    /// non-dominated sorting, preferring units in sparse regions of a front
+    /// by their crowding distance. This is the default.
+    Nsga2,
+
+    /// SPEA2, where each unit is given a strength fitness from the units that
+    /// dominate it and a density from its distance to its neighbours, and an
+    /// archive of `archive_size` (> 0) units is kept from which parents are
+    /// chosen by binary tournament. Non-dominated units beyond the archive
+    /// size are truncated by removing those nearest to their neighbours.
+    Spea2 {
+        /// The number of units kept in the archive.
+        archive_size: usize,
+    },
+
+    /// MOEA/D, where the problem is decomposed into one scalar subproblem per
+    /// unit, each with its own weight vector. Each child is bred from the
+    /// units of the `neighbourhood` (> 1, <= size) subproblems with the
+    /// nearest weights, and replaces the neighbouring unit it improves upon
+    /// the most.
+    MoeaD {
+        /// The number of subproblems in each neighbourhood.
+        neighbourhood: usize,
+
+        /// How the objectives are combined into the value of a subproblem.
+        decomposition: Decomposition,
+    },
+}
+
+/// How MOEA/D combines the objectives of a unit into a single value for a
+/// subproblem, given its weight vector and the best value of each objective
+/// seen so far.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Decomposition {
+    /// The greatest weighted distance of any objective from its best value,
+    /// which finds units on concave fronts. This is the default.
+    #[default]
+    Tchebycheff,
+
+    /// The weighted sum of the objectives, which only finds units on convex
+    /// regions of a front.
+    WeightedSum,
+}
+
+/// MultiObjectivePopulation evolves a collection of multi-objective units,
+/// seeking the Pareto front of units where no objective can be improved
+/// without worsening another. Units are evolved with NSGA-II by default, and
+/// SPEA2 or MOEA/D can be chosen instead with `set_algorithm`.
+pub struct MultiObjectivePopulation<T: MultiObjectiveUnit> {
+    units: Vec<LazyUnit<Objectives<T>>>,
+    ranks: Vec<usize>,
+    scores: Vec<(usize, f64)>,
+    subproblems: Vec<Subproblem>,
+    ideal: Vec<f64>,
+
+    seed: usize,
+    max_size: usize,
+    algorithm: Algorithm,
+    objectives: Vec<Objective>,
+    generations: u32,
+    evaluations: usize,
+    archive: Option<ParetoArchive<T>>,
+    thread_pool: Option<ThreadPool>,
+    failure_policy: FailurePolicy,
+}
+
+/// A scalar subproblem of MOEA/D.
+struct Subproblem {
+    weights: Vec<f64>,
+    neighbours: Vec<usize>,
+}
+
+impl<T: MultiObjectiveUnit> MultiObjectivePopulation<T> {
+    /// Creates a new population from an initial group of units.
+    pub fn new(init_pop: Vec<T>) -> Self {
+        MultiObjectivePopulation {
+            units: init_pop
+                .into_iter()
+                .map(|unit| {
+                    LazyUnit {
+                        unit: Objectives(unit),
+                        lazy_fitness: None,
+                    }
+                })
+                .collect(),
+            ranks: Vec::new(),
+            scores: Vec::new(),
+            subproblems: Vec::new(),
+            ideal: Vec::new(),
+            seed: 1,
+            max_size: 100,
+            algorithm: Algorithm::Nsga2,
+            objectives: Vec::new(),
+            generations: 0,
+            evaluations: 0,
+            archive: None,
+            thread_pool: None,
+            failure_policy: FailurePolicy::Abort,
+        }
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Sets the random seed of the population, in the same way as
+    /// `Population::set_rand_seed`.
+    pub fn set_rand_seed(&mut self, seed: usize) -> &mut Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Sets the size (> 0) of the population, which is the number of children
+    /// bred each epoch. With MOEA/D it is also the number of subproblems, which
+    /// are recreated on the next run if the size changes, and must be at least
+    /// the neighbourhood.
+    pub fn set_size(&mut self, size: usize) -> &mut Self {
+        self.try_set_size(size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Sets the size of the population in the same way as `set_size`,
+    /// returning an error if it is invalid.
+    pub fn try_set_size(&mut self, size: usize) -> Result<&mut Self, PopulationError> {
+        if size == 0 {
+            return Err(PopulationError::InvalidSize(size));
+        }
+        if let Algorithm::MoeaD { neighbourhood, .. } = self.algorithm {
+            if neighbourhood > size {
+                return Err(PopulationError::InvalidNeighbourhood { neighbourhood, size });
+            }
+        }
+        if size != self.max_size {
+            self.ranks.clear();
+            self.subproblems.clear();
+        }
+        self.max_size = size;
+        Ok(self)
+    }
+
+    /// Sets the algorithm with which units are evolved. Defaults to
+    /// `Algorithm::Nsga2`.
+    pub fn set_algorithm(&mut self, algorithm: Algorithm) -> &mut Self {
+        self.try_set_algorithm(algorithm)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Sets the algorithm in the same way as `set_algorithm`, returning an
+    /// error if the archive size is zero, or if the neighbourhood is not
+    /// within (1, size].
+    pub fn try_set_algorithm(
+        &mut self,
+        algorithm: Algorithm,
+    ) -> Result<&mut Self, PopulationError> {
+        match algorithm {
+            Algorithm::Nsga2 => {}
+            Algorithm::Spea2 { archive_size } => {
+                if archive_size == 0 {
+                    return Err(PopulationError::InvalidArchiveSize(archive_size));
+                }
+            }
+            Algorithm::MoeaD { neighbourhood, .. } => {
+                if neighbourhood <= 1 || neighbourhood > self.max_size {
+                    return Err(PopulationError::InvalidNeighbourhood {
+                        neighbourhood,
+                        size: self.max_size,
+                    });
+                }
+            }
+        }
+        self.algorithm = algorithm;
+        self.ranks.clear();
+        self.subproblems.clear();
+        Ok(self)
+    }
+
+    /// Sets whether each objective, in the order returned by
+    /// `MultiObjectiveUnit::objectives`, is maximized or minimized. By default
+    /// every objective is maximized.
+    pub fn set_objectives(&mut self, objectives: Vec<Objective>) -> &mut Self {
+        self.objectives = objectives;
+        self
+    }
+
+    /// Sets an archive, which keeps the non-dominated units found across every
+    /// generation and can be obtained with `archive` after a run. The
+    /// objectives of the archive should match those of the population.
+    pub fn set_archive(&mut self, archive: ParetoArchive<T>) -> &mut Self {
+        self.archive = Some(archive);
+        self
+    }
+
+    /// Sets a thread pool on which the objectives of units are calculated, in
+    /// the same way as `Population::set_thread_pool`.
+    pub fn set_thread_pool(&mut self, thread_pool: &ThreadPool) -> &mut Self {
+        self.thread_pool = Some(thread_pool.clone());
+        self
+    }
+
+    /// Sets what happens to units whose objectives fail to be calculated, or
+    /// contain NaN. Defaults to `FailurePolicy::Abort`. With
+    /// `FailurePolicy::WorstFitness` such units are given the worst value of
+    /// each objective across the units of their generation.
+    pub fn set_failure_policy(&mut self, failure_policy: FailurePolicy) -> &mut Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Returns whether each of `n` objectives is maximized or minimized.
+    fn directions(&self, n: usize) -> Vec<Objective> {
+        if self.objectives.is_empty() {
+            vec![Objective::Maximize; n]
+        } else {
+            self.objectives.clone()
+        }
+    }
+
+    /// Checks that every evaluated unit has as many objectives as were set
+    /// with `set_objectives`, or as each other when none were set.
+    fn check_objectives(&self) -> Result<(), PopulationError> {
+        let mut counts = self.units
+            .iter()
+            .filter_map(|u| u.lazy_fitness.as_ref())
+            .map(Vec::len);
+        let expected = if self.objectives.is_empty() {
+            match counts.next() {
+                Some(n) => n,
+                None => return Ok(()),
+            }
+        } else {
+            self.objectives.len()
+        };
+        match counts.find(|&found| found != expected) {
+            Some(found) => Err(PopulationError::ObjectiveCountMismatch { expected, found }),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the objectives of each evaluated unit, along with whether each
+    /// objective is maximized or minimized.
+    fn points(&self) -> (Vec<&[f64]>, Vec<Objective>) {
+        let points: Vec<&[f64]> = self.units
+            .iter()
+            .map(|u| u.lazy_fitness.as_ref().expect("unit was not evaluated").as_slice())
+            .collect();
+        let n = points.first().map_or(0, |p| p.len());
+        assert!(
+            points.iter().all(|p| p.len() == n),
+            "units have differing numbers of objectives"
+        );
+        (points, self.directions(n))
+    }
+
+    /// Calculates the objectives of units that have not been evaluated yet.
+    /// Units that fail to be evaluated and are discarded by the failure policy
+    /// are left without objectives.
+    fn evaluate_pending(&mut self) -> Result<(), PopulationError> {
+        let pending = self.units
+            .iter()
+            .filter(|u| u.lazy_fitness.is_none())
+            .count();
+        let attempts = match self.failure_policy {
+            FailurePolicy::Retry(retries) => retries.saturating_add(1),
+            _ => 1,
+        };
+        let failures = evaluator::evaluate(&mut self.units, self.thread_pool.as_ref(), attempts, true);
+        self.evaluations += pending;
+        self.check_objectives()?;
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Ok(self.handle_failures(failures)?)
+        }
+    }
+
+    /// Applies the failure policy to units that failed to be evaluated, which
+    /// are left without objectives.
+    fn handle_failures(&mut self, mut failures: Vec<Failure>) -> Result<(), EvaluationError> {
+        let any_evaluated = self.units.iter().any(|u| u.lazy_fitness.is_some());
+
+        match self.failure_policy {
+            FailurePolicy::WorstFitness if any_evaluated => {
+                let evaluated: Vec<&Vec<f64>> = self.units
+                    .iter()
+                    .filter_map(|u| u.lazy_fitness.as_ref())
+                    .collect();
+                let worst: Vec<f64> = self.directions(evaluated[0].len())
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, direction)| *direction.worst(evaluated.iter().map(|o| &o[i])).unwrap())
+                    .collect();
+                for unit in self.units.iter_mut().filter(|u| u.lazy_fitness.is_none()) {
+                    unit.lazy_fitness = Some(worst.clone());
+                }
+                Ok(())
+            }
+            FailurePolicy::Discard if any_evaluated => Ok(()),
+            _ => Err(EvaluationError {
+                generation: self.generations,
+                failed: failures.len(),
+                failure: failures.remove(0),
+            }),
+        }
+    }
+
+    /// Calculates the objectives of units that have not been evaluated yet,
+    /// and decides which units survive with the algorithm of the population.
+    fn evaluate(&mut self) -> Result<(), PopulationError> {
+        self.evaluate_pending()?;
+
+        match self.algorithm {
+            Algorithm::Nsga2 => {
+                self.units.retain(|u| u.lazy_fitness.is_some());
+                self.select_nsga2();
+            }
+            Algorithm::Spea2 { archive_size } => {
+                self.units.retain(|u| u.lazy_fitness.is_some());
+                self.select_spea2(archive_size);
+            }
+            Algorithm::MoeaD { neighbourhood, decomposition } => {
+                if self.subproblems.len() == self.max_size {
+                    self.replace_moead(decomposition);
+                } else {
+                    self.initialize_moead(neighbourhood, decomposition)?;
+                }
+            }
+        }
+        self.rank();
+
+        if let Some(mut archive) = self.archive.take() {
+            archive.record(self.pareto_front(), self.generations);
+            self.archive = Some(archive);
+        }
+        Ok(())
+    }
+
+    /// Records the index of the non-dominated front of each unit.
+    fn rank(&mut self) {
+        let mut ranks = vec![0; self.units.len()];
+        {
+            let (points, directions) = self.points();
+            for (rank, front) in pareto::non_dominated_sort(&points, &directions).into_iter().enumerate() {
+                for i in front {
+                    ranks[i] = rank;
+                }
+            }
+        }
+        self.ranks = ranks;
+    }
+
+    /// Reorders the units such that the unit at each index of `order` comes
+    /// first, dropping the rest.
+    fn reorder(&mut self, order: &[usize]) {
+        let mut units: Vec<Option<_>> = mem::take(&mut self.units).into_iter().map(Some).collect();
+        self.units = order
+            .iter()
+            .map(|&i| units[i].take().unwrap())
+            .collect();
+    }
+
+    /// Chooses a parent by binary tournament, where the unit with the lesser
+    /// score wins.
+    fn tournament(&self, rng: &mut StdRng) -> usize {
+        let range = Range::new(0, self.units.len());
+        let a = range.ind_sample(rng);
+        let b = range.ind_sample(rng);
+        match self.scores[b].partial_cmp(&self.scores[a]) {
+            Some(Ordering::Less) => b,
+            _ => a,
+        }
+    }
+
+    /// Breeds a generation of children and adds them to the population.
+    fn epoch(&mut self) {
+        let seed: &[_] = &[self.seed, self.generations as usize];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let children: Vec<LazyUnit<Objectives<T>>> = (0..self.max_size)
+            .map(|i| {
+                let (a, b) = match self.algorithm {
+                    Algorithm::MoeaD { .. } => {
+                        let neighbours = &self.subproblems[i].neighbours;
+                        let range = Range::new(0, neighbours.len());
+                        (neighbours[range.ind_sample(&mut rng)], neighbours[range.ind_sample(&mut rng)])
+                    }
+                    _ => (self.tournament(&mut rng), self.tournament(&mut rng)),
+                };
+                let mut child_rng: XorShiftRng = rng.gen();
+                LazyUnit {
+                    unit: self.units[a].unit.breed_with_rng(&self.units[b].unit, &mut child_rng),
+                    lazy_fitness: None,
+                }
+            })
+            .collect();
+        self.units.extend(children);
+        self.generations += 1;
+    }
+
+    /// Runs up to `n_epochs` epochs.
+    fn run(&mut self, n_epochs: u32) -> Result<(), PopulationError> {
+        if self.units.is_empty() {
+            return Err(PopulationError::EmptyPopulation);
+        }
+        if self.ranks.len() != self.units.len() {
+            self.evaluate()?;
+        }
+        for _ in 0..n_epochs {
+            self.epoch();
+            self.evaluate()?;
+        }
+        Ok(())
+    }
+
+    /// Runs a number of epochs, calculating objectives on the thread pool set
+    /// with `set_thread_pool` if any, otherwise on a single process.
+    ///
+    /// Panics if the population is empty, if units have the wrong number of
+    /// objectives, or if the objectives of units fail and the failure policy
+    /// ends the run, see `try_epochs`.
+    pub fn epochs(&mut self, n_epochs: u32) -> &mut Self {
+        self.try_epochs(n_epochs).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs a number of epochs in the same way as `epochs`, returning an error
+    /// if the population is empty, if units do not have as many objectives as
+    /// were set with `set_objectives` or as each other, or if the objectives of
+    /// units fail and the failure policy ends the run.
+    pub fn try_epochs(&mut self, n_epochs: u32) -> Result<&mut Self, PopulationError> {
+        self.run(n_epochs)?;
+        Ok(self)
+    }
+
+    /// Runs a number of epochs where objectives are calculated across n
+    /// parallel processes, in the same way as `Population::epochs_parallel`.
+    pub fn epochs_parallel(&mut self, n_epochs: u32, n_processes: u32) -> &mut Self {
+        self.try_epochs_parallel(n_epochs, n_processes)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs a number of epochs in the same way as `epochs_parallel`, returning
+    /// an error if the number of processes is zero, if the population is empty,
+    /// if units have the wrong number of objectives, or if the objectives of
+    /// units fail and the failure policy ends the run.
+    pub fn try_epochs_parallel(
+        &mut self,
+        n_epochs: u32,
+        n_processes: u32,
+    ) -> Result<&mut Self, PopulationError> {
+        if n_processes == 0 {
+            return Err(PopulationError::InvalidThreads(n_processes));
+        }
+        let thread_pool = ThreadPool::new(n_processes as usize);
+        let previous = self.thread_pool.replace(thread_pool);
+        let result = self.run(n_epochs);
+        self.thread_pool = previous;
+        result.map(move |_| self)
+    }
+
+    //--------------------------------------------------------------------------
+
+    /// Returns the number of new generations that have been bred.
+    pub fn generations(&self) -> u32 {
+        self.generations
+    }
+
+    /// Returns the total number of objective evaluations performed.
+    pub fn evaluations(&self) -> usize {
+        self.evaluations
+    }
+
+    /// Returns the archive set with `set_archive`, containing the
+    /// non-dominated units found so far.
+    pub fn archive(&self) -> Option<&ParetoArchive<T>> {
+        self.archive.as_ref()
+    }
+
+    /// Returns the units of the Pareto front of the population along with
+    /// their objectives, which is empty until the population has been
+    /// evaluated by a run.
+    pub fn pareto_front(&self) -> impl Iterator<Item = (&T, &[f64])> {
+        self.units
+            .iter()
+            .zip(&self.ranks)
+            .filter(|&(_, &rank)| rank == 0)
+            .map(|(u, _)| (&u.unit.0, u.lazy_fitness.as_ref().unwrap().as_slice()))
+    }
+
+    /// Returns the full population of units along with their objectives and
+    /// front, ordered such that the Pareto front comes first. The current
+    /// generation is evaluated first if needed.
+    pub fn finish(&mut self) -> Vec<Solution<T>> {
+        if !self.units.is_empty() && self.ranks.len() != self.units.len() {
+            self.evaluate().unwrap_or_else(|err| panic!("{}", err));
+        }
+
+        let ranks = mem::take(&mut self.ranks);
+        self.scores.clear();
+        self.subproblems.clear();
+        let mut solutions: Vec<Solution<T>> = mem::take(&mut self.units)
+            .into_iter()
+            .zip(ranks)
+            .map(|(u, rank)| {
+                Solution {
+                    unit: u.unit.0,
+                    objectives: u.lazy_fitness.unwrap(),
+                    rank,
+                }
+            })
+            .collect();
+        solutions.sort_by_key(|s| s.rank);
+        solutions
+    }
+}
+
+//------------------------------------------------------------------------------
+
+impl<T: MultiObjectiveUnit> MultiObjectivePopulation<T> {
+    /// Reduces the evaluated units to the size of the population with NSGA-II,
+    /// taking whole non-dominated fronts in turn and splitting the last front
+    /// that fits by crowding distance.
+    fn select_nsga2(&mut self) {
+        let mut order = Vec::with_capacity(self.max_size);
+        let mut scores = Vec::with_capacity(self.max_size);
+        {
+            let (points, directions) = self.points();
+            for (rank, front) in pareto::non_dominated_sort(&points, &directions).into_iter().enumerate() {
+                let remaining = self.max_size - order.len();
+                if remaining == 0 {
+                    break;
+                }
+
+                let mut members: Vec<(usize, f64)> = front
+                    .iter()
+                    .cloned()
+                    .zip(crowding_distances(&points, &front))
+                    .collect();
+                if members.len() > remaining {
+                    members.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal));
+                    members.truncate(remaining);
+                }
+                for (index, distance) in members {
+                    order.push(index);
+                    scores.push((rank, -distance));
+                }
+            }
+        }
+        self.reorder(&order);
+        self.scores = scores;
+    }
+
+    /// Reduces the evaluated units to an archive of `archive_size` with SPEA2,
+    /// keeping every non-dominated unit if they fit, otherwise truncating them
+    /// by distance to their neighbours, and otherwise filling the archive with
+    /// the dominated units of the best fitness.
+    fn select_spea2(&mut self, archive_size: usize) {
+        let (order, scores) = {
+            let (points, directions) = self.points();
+            let n = points.len();
+
+            let dominates = |i: usize, j: usize| pareto::dominates(points[i], points[j], &directions);
+            let strength: Vec<usize> = (0..n)
+                .map(|i| (0..n).filter(|&j| dominates(i, j)).count())
+                .collect();
+            let raw: Vec<usize> = (0..n)
+                .map(|i| (0..n).filter(|&j| dominates(j, i)).map(|j| strength[j]).sum())
+                .collect();
+
+            // The density of a unit is from the distance to its k-th nearest
+            // neighbour, where k is the square root of the number of units.
+            let distances: Vec<Vec<f64>> = (0..n)
+                .map(|i| (0..n).map(|j| distance(points[i], points[j])).collect())
+                .collect();
+            let k = ((n as f64).sqrt() as usize).max(1);
+            let fitness: Vec<f64> = (0..n)
+                .map(|i| {
+                    let mut nearest: Vec<f64> = (0..n).filter(|&j| j != i).map(|j| distances[i][j]).collect();
+                    nearest.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+                    let sigma = nearest.get(k - 1).or_else(|| nearest.last()).cloned().unwrap_or(0.0);
+                    raw[i] as f64 + 1.0 / (sigma + 2.0)
+                })
+                .collect();
+
+            let mut order: Vec<usize> = (0..n).collect();
+            order.sort_by(|&a, &b| fitness[a].partial_cmp(&fitness[b]).unwrap_or(Ordering::Equal));
+            let non_dominated = order.iter().take_while(|&&i| raw[i] == 0).count();
+            if non_dominated > archive_size {
+                order.truncate(non_dominated);
+                truncate_by_distance(&mut order, &distances, archive_size);
+            } else {
+                order.truncate(archive_size);
+            }
+
+            let scores = order.iter().map(|&i| (0, fitness[i])).collect();
+            (order, scores)
+        };
+        self.reorder(&order);
+        self.scores = scores;
+    }
+
+    /// Decomposes the problem into a subproblem for each unit of the
+    /// population with MOEA/D, breeding from the initial units until there is
+    /// a unit for every subproblem, and gives each subproblem the unit that
+    /// solves it best.
+    fn initialize_moead(
+        &mut self,
+        neighbourhood: usize,
+        decomposition: Decomposition,
+    ) -> Result<(), PopulationError> {
+        let seed: &[_] = &[self.seed, self.generations as usize, self.max_size];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        self.units.retain(|u| u.lazy_fitness.is_some());
+        while self.units.len() < self.max_size {
+            if self.units.is_empty() {
+                return Ok(());
+            }
+            let range = Range::new(0, self.units.len());
+            let children: Vec<LazyUnit<Objectives<T>>> = (self.units.len()..self.max_size)
+                .map(|_| {
+                    let a = range.ind_sample(&mut rng);
+                    let b = range.ind_sample(&mut rng);
+                    let mut child_rng: XorShiftRng = rng.gen();
+                    LazyUnit {
+                        unit: self.units[a].unit.breed_with_rng(&self.units[b].unit, &mut child_rng),
+                        lazy_fitness: None,
+                    }
+                })
+                .collect();
+            self.units.extend(children);
+            self.evaluate_pending()?;
+            self.units.retain(|u| u.lazy_fitness.is_some());
+        }
+
+        let order = {
+            let (points, directions) = self.points();
+            let points: Vec<Vec<f64>> = points.iter().map(|p| minimized(p, &directions)).collect();
+            let weights = weight_vectors(self.max_size, directions.len(), &mut rng);
+            self.ideal = ideal_point(points.iter().map(Vec::as_slice));
+
+            self.subproblems = weights
+                .iter()
+                .map(|w| {
+                    let mut neighbours: Vec<usize> = (0..weights.len()).collect();
+                    neighbours.sort_by(|&a, &b| {
+                        distance(w, &weights[a])
+                            .partial_cmp(&distance(w, &weights[b]))
+                            .unwrap_or(Ordering::Equal)
+                    });
+                    neighbours.truncate(neighbourhood);
+                    Subproblem {
+                        weights: w.clone(),
+                        neighbours,
+                    }
+                })
+                .collect();
+
+            let mut remaining: Vec<usize> = (0..points.len()).collect();
+            let mut order = Vec::with_capacity(self.max_size);
+            for subproblem in &self.subproblems {
+                let value = |i: usize| decomposition.value(&points[i], &subproblem.weights, &self.ideal);
+                let best = (0..remaining.len())
+                    .min_by(|&a, &b| {
+                        value(remaining[a])
+                            .partial_cmp(&value(remaining[b]))
+                            .unwrap_or(Ordering::Equal)
+                    })
+                    .unwrap();
+                order.push(remaining.remove(best));
+            }
+            order
+        };
+        self.reorder(&order);
+        Ok(())
+    }
+
+    /// Offers each child bred for a subproblem to the neighbouring subproblems
+    /// with MOEA/D, where it replaces the unit of the neighbour it improves
+    /// upon the most. Children that failed to be evaluated are dropped.
+    fn replace_moead(&mut self, decomposition: Decomposition) {
+        let children = self.units.split_off(self.subproblems.len());
+        let directions = self.directions(self.ideal.len());
+
+        for (k, child) in children.into_iter().enumerate() {
+            let point = match child.lazy_fitness {
+                Some(ref objectives) => minimized(objectives, &directions),
+                None => continue,
+            };
+            for (ideal, &value) in self.ideal.iter_mut().zip(&point) {
+                *ideal = ideal.min(value);
+            }
+
+            let improvement = |j: usize| {
+                let subproblem: &Subproblem = &self.subproblems[j];
+                let current = minimized(self.units[j].lazy_fitness.as_ref().unwrap(), &directions);
+                decomposition.value(&current, &subproblem.weights, &self.ideal)
+                    - decomposition.value(&point, &subproblem.weights, &self.ideal)
+            };
+            let best = self.subproblems[k]
+                .neighbours
+                .iter()
+                .map(|&j| (j, improvement(j)))
+                .filter(|&(_, improvement)| improvement > 0.0)
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+            if let Some((j, _)) = best {
+                self.units[j] = child;
+            }
+        }
+    }
+}
+
+impl Decomposition {
+    /// Returns the value of a point for a subproblem, where lesser is better,
+    /// given points and the ideal point on a scale where lesser is better.
+    fn value(self, point: &[f64], weights: &[f64], ideal: &[f64]) -> f64 {
+        let terms = point.iter().zip(weights).zip(ideal);
+        match self {
+            Decomposition::Tchebycheff => terms
+                .map(|((x, w), z)| w.max(1e-6) * (x - z).abs())
+                .fold(f64::NEG_INFINITY, f64::max),
+            Decomposition::WeightedSum => terms.map(|((x, w), _)| w * x).sum(),
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Maps objectives onto a scale where lesser is always better.
+fn minimized(objectives: &[f64], directions: &[Objective]) -> Vec<f64> {
+    objectives
+        .iter()
+        .zip(directions)
+        .map(|(&x, direction)| -direction.orient(x))
+        .collect()
+}
+
+/// Returns the least value of each objective across points.
+fn ideal_point<'a, I: IntoIterator<Item = &'a [f64]>>(points: I) -> Vec<f64> {
+    let mut ideal: Vec<f64> = Vec::new();
+    for point in points {
+        if ideal.is_empty() {
+            ideal = point.to_vec();
+        }
+        for (ideal, &value) in ideal.iter_mut().zip(point) {
+            *ideal = ideal.min(value);
+        }
+    }
+    ideal
+}
+
+/// Returns the Euclidean distance between two points.
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Returns `n` weight vectors for `m` objectives, each summing to one. As many
+/// as possible are spread evenly on a simplex lattice, and the rest are drawn
+/// at random.
+fn weight_vectors(n: usize, m: usize, rng: &mut StdRng) -> Vec<Vec<f64>> {
+    // The number of lattice points with h divisions is (h + m - 1) choose
+    // (m - 1).
+    let lattice_size = |h: usize| (1..m).fold(1.0, |c, i| c * (h + i) as f64 / i as f64);
+    let mut divisions = 0;
+    while lattice_size(divisions + 1) <= n as f64 {
+        divisions += 1;
+    }
+
+    let mut weights = if divisions == 0 {
+        Vec::new()
+    } else {
+        lattice(divisions, m)
+            .into_iter()
+            .map(|point| point.into_iter().map(|x| x as f64 / divisions as f64).collect())
+            .collect()
+    };
+    while weights.len() < n {
+        let draws: Vec<f64> = (0..m).map(|_| -(1.0 - rng.gen::<f64>()).ln()).collect();
+        let total: f64 = draws.iter().sum();
+        weights.push(draws.into_iter().map(|x| x / total).collect());
+    }
+    weights
+}
+
+/// Returns every way of dividing `h` between `m` objectives.
+fn lattice(h: usize, m: usize) -> Vec<Vec<usize>> {
+    if m == 1 {
+        return vec![vec![h]];
+    }
+    let mut points = Vec::new();
+    for i in 0..=h {
+        for mut rest in lattice(h - i, m - 1) {
+            rest.insert(0, i);
+            points.push(rest);
+        }
+    }
+    points
+}
+
+/// Removes units from `order` until `size` remain, each time removing the
+/// unit nearest to its neighbours, where ties on the nearest neighbour are
+/// broken by the next nearest and so on.
+fn truncate_by_distance(order: &mut Vec<usize>, distances: &[Vec<f64>], size: usize) {
+    while order.len() > size {
+        let neighbours = |i: usize| {
+            let mut nearest: Vec<f64> = order
+                .iter()
+                .filter(|&&j| j != i)
+                .map(|&j| distances[i][j])
+                .collect();
+            nearest.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            nearest
+        };
+        let crowded = (0..order.len())
+            .map(|position| (position, neighbours(order[position])))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal))
+            .unwrap()
+            .0;
+        order.remove(crowded);
+    }
+}
+
+/// Returns the crowding distance of each point of a front, which is the sum
+/// across objectives of the normalised distance between its neighbours. The
+/// points at the extremes of each objective have an infinite distance.
+pub(crate) fn crowding_distances<P: AsRef<[f64]>>(points: &[P], front: &[usize]) -> Vec<f64> {
+    let n = front.len();
+    if n <= 2 {
+        return vec![f64::INFINITY; n];
+    }
+
+    let mut distances = vec![0.0; n];
+    let mut order: Vec<usize> = (0..n).collect();
+    let columns = (0..points[front[0]].as_ref().len()).map(|objective| {
+        front
+            .iter()
+            .map(|&p| points[p].as_ref()[objective])
+            .collect::<Vec<f64>>()
+    });
+    for values in columns {
+        order.sort_by(|&a, &b| values[a].partial_cmp(&values[b]).unwrap_or(Ordering::Equal));
+
+        let range = values[order[n - 1]] - values[order[0]];
+        distances[order[0]] = f64::INFINITY;
+        distances[order[n - 1]] = f64::INFINITY;
+        if range > 0.0 {
+            for i in 1..(n - 1) {
+                distances[order[i]] += (values[order[i + 1]] - values[order[i - 1]]) / range;
+            }
+        }
+    }
+    distances
+}
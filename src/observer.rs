@@ -0,0 +1,99 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use unit::Unit;
+use population::LazyUnit;
+use report::GenerationStats;
+
+/// Signals whether a run of epochs should carry on after a generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Control {
+    /// Carry on with the run.
+    Continue,
+
+    /// End the run with the current generation.
+    Stop,
+}
+
+/// Observer is notified of each generation of a population once it has been
+/// evaluated and sorted, and is able to stop the run early.
+///
+/// Any closure of the form `FnMut(&GenerationView<T>) -> Control` is also an
+/// observer.
+pub trait Observer<T: Unit>: Send {
+    /// Called after each generation has been evaluated.
+    fn on_generation(&mut self, view: &GenerationView<T>) -> Control;
+}
+
+impl<T: Unit, F> Observer<T> for F
+where
+    F: FnMut(&GenerationView<T>) -> Control + Send,
+{
+    fn on_generation(&mut self, view: &GenerationView<T>) -> Control {
+        self(view)
+    }
+}
+
+/// GenerationView provides read access to an evaluated generation of units
+/// along with their fitness.
+pub struct GenerationView<'a, T: Unit + 'a> {
+    units: &'a [LazyUnit<T>],
+    stats: &'a GenerationStats<T::Fitness>,
+}
+
+impl<'a, T: Unit + 'a> GenerationView<'a, T> {
+    /// Creates a view of units that are sorted from strongest to weakest.
+    pub(crate) fn new(units: &'a [LazyUnit<T>], stats: &'a GenerationStats<T::Fitness>) -> Self {
+        GenerationView { units, stats }
+    }
+
+    /// Returns the generation number, where 0 is the initial population.
+    pub fn generation(&self) -> u32 {
+        self.stats.generation
+    }
+
+    /// Returns the fitness stats of the generation.
+    pub fn stats(&self) -> &GenerationStats<T::Fitness> {
+        self.stats
+    }
+
+    /// Returns the number of units in the generation.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns true if the generation contains no units.
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Returns the strongest unit of the generation and its fitness.
+    pub fn best(&self) -> Option<(&T, &T::Fitness)> {
+        self.iter().next()
+    }
+
+    /// Returns an iterator over the units of the generation and their fitness,
+    /// ordered such that the first element is the strongest candidate.
+    pub fn iter(&self) -> impl Iterator<Item = (&T, &T::Fitness)> {
+        self.units
+            .iter()
+            .filter_map(|u| u.lazy_fitness.as_ref().map(|f| (&u.unit, f)))
+    }
+}
@@ -0,0 +1,453 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+
+//! Tools for working with Pareto fronts: dominance between the objectives of
+//! units, an archive of the non-dominated units found over a run, and quality
+//! indicators for comparing fronts numerically.
+//!
+//! Points are the objective values of units, and each function is given
+//! whether each objective is maximized or minimized.
+
+use fitness::Objective;
+
+use std::cmp::Ordering;
+use std::f64;
+use std::mem;
+
+/// Returns true if `a` dominates `b`, where `a` is no worse than `b` in every
+/// objective and strictly better in at least one.
+pub fn dominates(a: &[f64], b: &[f64], objectives: &[Objective]) -> bool {
+    let mut better = false;
+    for ((x, y), objective) in a.iter().zip(b).zip(objectives) {
+        match objective.cmp(x, y) {
+            Ordering::Less => return false,
+            Ordering::Greater => better = true,
+            Ordering::Equal => {}
+        }
+    }
+    better
+}
+
+/// Returns true if `a` epsilon-dominates `b`, where `a` would dominate or equal
+/// `b` after being improved by `epsilon[i]` in each objective `i`.
+pub fn epsilon_dominates(a: &[f64], b: &[f64], epsilon: &[f64], objectives: &[Objective]) -> bool {
+    a.iter()
+        .zip(b)
+        .zip(epsilon)
+        .zip(objectives)
+        .all(|(((&x, &y), &e), objective)| objective.orient(x) + e >= objective.orient(y))
+}
+
+/// Sorts points into non-dominated fronts, returning the indexes of the points
+/// of each front. The first front is the Pareto front, dominated by no other
+/// point, and each later front is dominated only by points of earlier fronts.
+pub fn non_dominated_sort<P: AsRef<[f64]>>(points: &[P], objectives: &[Objective]) -> Vec<Vec<usize>> {
+    let mut dominated: Vec<Vec<usize>> = vec![Vec::new(); points.len()];
+    let mut dominators = vec![0usize; points.len()];
+    let mut front = Vec::new();
+
+    for p in 0..points.len() {
+        for q in 0..points.len() {
+            if dominates(points[p].as_ref(), points[q].as_ref(), objectives) {
+                dominated[p].push(q);
+            } else if dominates(points[q].as_ref(), points[p].as_ref(), objectives) {
+                dominators[p] += 1;
+            }
+        }
+        if dominators[p] == 0 {
+            front.push(p);
+        }
+    }
+
+    let mut fronts = Vec::new();
+    while !front.is_empty() {
+        let mut next = Vec::new();
+        for &p in &front {
+            for &q in &dominated[p] {
+                dominators[q] -= 1;
+                if dominators[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+        fronts.push(mem::replace(&mut front, next));
+    }
+    fronts
+}
+
+//------------------------------------------------------------------------------
+
+/// A unit kept by a `ParetoArchive`.
+#[derive(Clone, Debug)]
+pub struct Entry<T> {
+    /// The unit.
+    pub unit: T,
+
+    /// The value of each objective of the unit.
+    pub objectives: Vec<f64>,
+
+    /// The generation in which the unit was archived.
+    pub generation: u32,
+}
+
+/// ParetoArchive maintains the units that are not dominated by any other unit
+/// given to it, which survive even when those units are lost from the
+/// population itself.
+///
+/// Optionally the archive can be pruned by epsilon-dominance, where objective
+/// space is divided into boxes of a given size and at most one unit is kept
+/// per box, which bounds the size of the archive and spreads its units across
+/// the front.
+pub struct ParetoArchive<T> {
+    objectives: Vec<Objective>,
+    epsilon: Option<Vec<f64>>,
+    entries: Vec<Entry<T>>,
+
+    // Captured by `new` in the same way as the functions of a `HallOfFame`.
+    clone: fn(&T) -> T,
+}
+
+impl<T: Clone> ParetoArchive<T> {
+    /// Creates an empty archive for units where each objective, in order, is
+    /// maximized or minimized. These should match the objectives of the
+    /// population the archive is used with.
+    pub fn new(objectives: Vec<Objective>) -> Self {
+        ParetoArchive {
+            objectives,
+            epsilon: None,
+            entries: Vec::new(),
+            clone: T::clone,
+        }
+    }
+}
+
+impl<T> ParetoArchive<T> {
+    /// Prunes the archive by epsilon-dominance, with a box size of
+    /// `epsilon[i]` (> 0) for each objective `i`.
+    pub fn with_epsilon(mut self, epsilon: Vec<f64>) -> Self {
+        assert!(epsilon.iter().all(|&e| e > 0.0));
+        self.epsilon = Some(epsilon);
+        self
+    }
+
+    /// Offers a unit and its objectives to the archive, returning true if it
+    /// was kept. Archived units that the new unit dominates are removed.
+    pub fn insert(&mut self, unit: T, objectives: Vec<f64>, generation: u32) -> bool {
+        let accepted = if self.epsilon.is_some() {
+            self.accept_epsilon(&objectives)
+        } else {
+            self.accept(&objectives)
+        };
+        if accepted {
+            self.entries.push(Entry {
+                unit,
+                objectives,
+                generation,
+            });
+        }
+        accepted
+    }
+
+    /// Removes the entries dominated by a point and returns true, unless the
+    /// point is dominated by or equal to an entry.
+    fn accept(&mut self, point: &[f64]) -> bool {
+        let objectives = &self.objectives;
+        if self.entries
+            .iter()
+            .any(|e| e.objectives == point || dominates(&e.objectives, point, objectives))
+        {
+            return false;
+        }
+        self.entries.retain(|e| !dominates(point, &e.objectives, objectives));
+        true
+    }
+
+    /// Removes the entries whose box is dominated by the box of a point, or
+    /// that share its box and are beaten by it, and returns true, unless the
+    /// box of the point is dominated by the box of an entry or an entry of the
+    /// same box beats it.
+    fn accept_epsilon(&mut self, point: &[f64]) -> bool {
+        let epsilon = self.epsilon.as_ref().unwrap();
+        let objectives = &self.objectives;
+        let corner = box_of(point, epsilon, objectives);
+
+        let mut replaced = false;
+        for entry in &self.entries {
+            let other = box_of(&entry.objectives, epsilon, objectives);
+            if other == corner {
+                // Within a box the dominant unit wins, otherwise the unit
+                // nearest to the corner of the box.
+                if dominates(&entry.objectives, point, objectives)
+                    || (!dominates(point, &entry.objectives, objectives)
+                        && corner_distance(&entry.objectives, &other, epsilon, objectives)
+                            <= corner_distance(point, &corner, epsilon, objectives))
+                {
+                    return false;
+                }
+                replaced = true;
+            } else if box_dominates(&other, &corner) {
+                return false;
+            }
+        }
+
+        self.entries.retain(|e| {
+            let other = box_of(&e.objectives, epsilon, objectives);
+            !(box_dominates(&corner, &other) || (replaced && other == corner))
+        });
+        true
+    }
+
+    /// Returns the archived units.
+    pub fn entries(&self) -> &[Entry<T>] {
+        &self.entries
+    }
+
+    /// Returns the number of archived units.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if no units have been archived.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Consumes the archive, returning its entries.
+    pub fn into_entries(self) -> Vec<Entry<T>> {
+        self.entries
+    }
+
+    /// Offers the units of the Pareto front of a generation to the archive.
+    pub(crate) fn record<'a, I>(&mut self, front: I, generation: u32)
+    where
+        T: 'a,
+        I: IntoIterator<Item = (&'a T, &'a [f64])>,
+    {
+        for (unit, objectives) in front {
+            let unit = (self.clone)(unit);
+            self.insert(unit, objectives.to_vec(), generation);
+        }
+    }
+}
+
+/// Returns the box of a point under epsilon-dominance, on a scale where lesser
+/// is better.
+fn box_of(point: &[f64], epsilon: &[f64], objectives: &[Objective]) -> Vec<f64> {
+    point
+        .iter()
+        .zip(epsilon)
+        .zip(objectives)
+        .map(|((&x, &e), objective)| (-objective.orient(x) / e).floor())
+        .collect()
+}
+
+/// Returns true if box `a` dominates box `b`, where lesser is better.
+fn box_dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a != b
+}
+
+/// Returns the distance of a point from the best corner of its box.
+fn corner_distance(point: &[f64], corner: &[f64], epsilon: &[f64], objectives: &[Objective]) -> f64 {
+    point
+        .iter()
+        .zip(corner)
+        .zip(epsilon)
+        .zip(objectives)
+        .map(|(((&x, &c), &e), objective)| (-objective.orient(x) - c * e).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
+
+//------------------------------------------------------------------------------
+
+/// Maps points onto a scale where lesser is better, keeping only those that
+/// are strictly better than a reference point in every objective.
+fn minimized<P: AsRef<[f64]>>(points: &[P], reference: &[f64], objectives: &[Objective]) -> (Vec<Vec<f64>>, Vec<f64>) {
+    let orient = |point: &[f64]| -> Vec<f64> {
+        point
+            .iter()
+            .zip(objectives)
+            .map(|(&x, objective)| -objective.orient(x))
+            .collect()
+    };
+    let reference = orient(reference);
+    let points = points
+        .iter()
+        .map(|p| orient(p.as_ref()))
+        .filter(|p| p.iter().zip(&reference).all(|(x, r)| x < r))
+        .collect();
+    (points, reference)
+}
+
+/// Returns the hypervolume of a set of points, which is the volume of
+/// objective space that they dominate, bounded by a reference point that
+/// should be worse than every point in every objective. Points that are not
+/// strictly better than the reference point contribute nothing, and a greater
+/// hypervolume indicates a better front.
+///
+/// Two and three objectives are calculated by sweeping through the points,
+/// and more objectives with the WFG algorithm.
+pub fn hypervolume<P: AsRef<[f64]>>(points: &[P], reference: &[f64], objectives: &[Objective]) -> f64 {
+    assert_eq!(reference.len(), objectives.len());
+    let (points, reference) = minimized(points, reference, objectives);
+    let fronts = non_dominated_sort(&points, &vec![Objective::Minimize; reference.len()]);
+    let front: Vec<Vec<f64>> = match fronts.into_iter().next() {
+        Some(front) => front.into_iter().map(|i| points[i].clone()).collect(),
+        None => return 0.0,
+    };
+
+    match reference.len() {
+        1 => reference[0] - front[0][0],
+        2 => hypervolume_2d(front, &reference),
+        3 => hypervolume_3d(front, &reference),
+        _ => wfg(front, &reference),
+    }
+}
+
+/// Returns the hypervolume of points in two dimensions, where lesser is
+/// better, by sweeping through them in order of the first objective.
+fn hypervolume_2d(mut points: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    points.sort_by(|a, b| a[0].partial_cmp(&b[0]).unwrap_or(Ordering::Equal));
+
+    let mut volume = 0.0;
+    let mut ceiling = reference[1];
+    for point in points {
+        if point[1] < ceiling {
+            volume += (reference[0] - point[0]) * (ceiling - point[1]);
+            ceiling = point[1];
+        }
+    }
+    volume
+}
+
+/// Returns the hypervolume of points in three dimensions, where lesser is
+/// better, by sweeping through them in order of the third objective and
+/// summing the slices between them.
+fn hypervolume_3d(mut points: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    points.sort_by(|a, b| a[2].partial_cmp(&b[2]).unwrap_or(Ordering::Equal));
+
+    let mut volume = 0.0;
+    for i in 0..points.len() {
+        let depth = points.get(i + 1).map_or(reference[2], |next| next[2]) - points[i][2];
+        if depth > 0.0 {
+            volume += hypervolume_2d(points[..(i + 1)].to_vec(), reference) * depth;
+        }
+    }
+    volume
+}
+
+/// Returns the hypervolume of points, where lesser is better, with the WFG
+/// algorithm, which sums the exclusive hypervolume of each point against the
+/// points after it.
+fn wfg(mut points: Vec<Vec<f64>>, reference: &[f64]) -> f64 {
+    // Sorting by the last objective keeps the limit sets small.
+    let last = reference.len() - 1;
+    points.sort_by(|a, b| b[last].partial_cmp(&a[last]).unwrap_or(Ordering::Equal));
+
+    let minimize = vec![Objective::Minimize; reference.len()];
+    let mut volume = 0.0;
+    for (i, point) in points.iter().enumerate() {
+        let inclusive: f64 = point.iter().zip(reference).map(|(x, r)| r - x).product();
+
+        // The points after this one, limited to the region it dominates.
+        let limited: Vec<Vec<f64>> = points[(i + 1)..]
+            .iter()
+            .map(|other| point.iter().zip(other).map(|(&x, &y)| x.max(y)).collect())
+            .collect();
+        let front: Vec<Vec<f64>> = match non_dominated_sort(&limited, &minimize).into_iter().next() {
+            Some(front) => front.into_iter().map(|j| limited[j].clone()).collect(),
+            None => Vec::new(),
+        };
+
+        volume += inclusive - if front.is_empty() { 0.0 } else { wfg(front, reference) };
+    }
+    volume
+}
+
+/// Returns the Euclidean distance between two points.
+fn distance(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum::<f64>().sqrt()
+}
+
+/// Returns the inverted generational distance of a front from a reference
+/// front, such as a sample of the true Pareto front, which is the mean distance
+/// from each reference point to its nearest point of the front. A lesser
+/// distance indicates a front that is both closer to and more evenly covers
+/// the reference front. Returns infinity if the front is empty.
+pub fn igd<P: AsRef<[f64]>, R: AsRef<[f64]>>(front: &[P], reference_front: &[R]) -> f64 {
+    let total: f64 = reference_front
+        .iter()
+        .map(|r| {
+            front
+                .iter()
+                .map(|p| distance(p.as_ref(), r.as_ref()))
+                .fold(f64::INFINITY, f64::min)
+        })
+        .sum();
+    total / reference_front.len() as f64
+}
+
+/// Returns the generalized spread of a front, which measures how evenly its
+/// points are distributed and how far they extend towards the extremes of a
+/// reference front, such as a sample of the true Pareto front. A spread of 0
+/// indicates an evenly spaced front reaching every extreme, and a greater
+/// spread a worse distribution. Returns NaN if the front has fewer than two
+/// points.
+pub fn spread<P: AsRef<[f64]>, R: AsRef<[f64]>>(
+    front: &[P],
+    reference_front: &[R],
+    objectives: &[Objective],
+) -> f64 {
+    if front.len() < 2 {
+        return f64::NAN;
+    }
+    let nearest = |point: &[f64], skip: Option<usize>| {
+        front
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| Some(i) != skip)
+            .map(|(_, p)| distance(p.as_ref(), point))
+            .fold(f64::INFINITY, f64::min)
+    };
+
+    // The extreme of each objective is the reference point best in it.
+    let extremes: f64 = objectives
+        .iter()
+        .enumerate()
+        .filter_map(|(m, objective)| {
+            reference_front
+                .iter()
+                .map(AsRef::as_ref)
+                .max_by(|a, b| objective.cmp(&a[m], &b[m]))
+        })
+        .map(|extreme| nearest(extreme, None))
+        .sum();
+
+    let distances: Vec<f64> = front
+        .iter()
+        .enumerate()
+        .map(|(i, p)| nearest(p.as_ref(), Some(i)))
+        .collect();
+    let mean = distances.iter().sum::<f64>() / distances.len() as f64;
+    let deviation: f64 = distances.iter().map(|d| (d - mean).abs()).sum();
+
+    (extremes + deviation) / (extremes + front.len() as f64 * mean)
+}
@@ -19,42 +19,52 @@
 // THE SOFTWARE.
 
 use unit::Unit;
+use selection::{Selection, Truncation};
+use report::{EvolutionReport, GenerationStats, TerminationReason};
+use observer::{Control, GenerationView, Observer};
+use termination::{Progress, Termination};
+use hall_of_fame::HallOfFame;
+use fitness::{Fitness, NonFinitePolicy, Objective};
+use evaluator::{self, EvaluationError, Failure, FailurePolicy, ThreadPool};
+#[cfg(feature = "serde")]
+use checkpoint::{self, CheckpointError};
 
-use crossbeam::scope;
-
-use rand::{SeedableRng, StdRng};
-use rand::distributions::{IndependentSample, Range};
+use rand::{Rng, SeedableRng, StdRng, XorShiftRng};
 
 use std::mem;
-use std::sync::{Arc, Mutex, Condvar};
-use std::cmp::Ordering;
-use std::sync::mpsc::sync_channel;
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::Serialize;
+#[cfg(feature = "serde")]
+use serde::de::DeserializeOwned;
 
 /// Wraps a unit within a struct that lazily evaluates its fitness to avoid
 /// duplicate work.
-struct LazyUnit<T: Unit> {
-    unit: T,
-    lazy_fitness: Option<f64>,
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: ::serde::Serialize, T::Fitness: ::serde::Serialize",
+        deserialize = "T: ::serde::Deserialize<'de>, T::Fitness: ::serde::Deserialize<'de>"
+    ))
+)]
+pub(crate) struct LazyUnit<T: Unit> {
+    pub(crate) unit: T,
+    pub(crate) lazy_fitness: Option<T::Fitness>,
 }
 
 impl<T: Unit> LazyUnit<T> {
     fn from(unit: T) -> Self {
         LazyUnit {
-            unit: unit,
+            unit,
             lazy_fitness: None,
         }
     }
-
-    fn fitness(&mut self) -> f64 {
-        match self.lazy_fitness {
-            Some(x) => x,
-            None => {
-                let fitness = self.unit.fitness();
-                self.lazy_fitness = Some(fitness);
-                fitness
-            }
-        }
-    }
 }
 
 /// Population is an abstraction that represents a collection of units. Each
@@ -65,12 +75,31 @@ impl<T: Unit> LazyUnit<T> {
 /// The population is responsible for iterating new generations of units by
 /// mating fit units and killing unfit units.
 pub struct Population<T: Unit> {
-    units: Vec<T>,
+    units: Vec<LazyUnit<T>>,
+    evaluated: bool,
 
     seed: usize,
     breed_factor: f64,
     survival_factor: f64,
     max_size: usize,
+    elitism: usize,
+    selection: Box<dyn Selection>,
+    report: EvolutionReport<T::Fitness>,
+    run_elapsed: Duration,
+    run_evaluations: usize,
+    observers: Vec<Box<dyn Observer<T>>>,
+    objective: Objective,
+    termination: Option<Box<dyn Termination<T::Fitness>>>,
+    hall_of_fame: Option<HallOfFame<T>>,
+    thread_pool: Option<ThreadPool>,
+    breed_parallel: Option<BreedParallel<T>>,
+    failure_policy: FailurePolicy,
+    non_finite_policy: NonFinitePolicy,
+
+    #[cfg(feature = "serde")]
+    autosave: Option<Autosave<T>>,
+    #[cfg(feature = "serde")]
+    checkpoint_error: Option<CheckpointError>,
 }
 
 impl<T: Unit> Population<T> {
@@ -79,28 +108,58 @@ impl<T: Unit> Population<T> {
     /// `set_population` before calling epochs.
     pub fn new(init_pop: Vec<T>) -> Self {
         Population {
-            units: init_pop,
+            units: init_pop.into_iter().map(LazyUnit::from).collect(),
+            evaluated: false,
             seed: 1,
             breed_factor: 0.5,
             survival_factor: 0.5,
             max_size: 100,
+            elitism: 0,
+            selection: Box::new(Truncation),
+            report: EvolutionReport::default(),
+            run_elapsed: Duration::default(),
+            run_evaluations: 0,
+            observers: Vec::new(),
+            objective: Objective::Maximize,
+            termination: None,
+            hall_of_fame: None,
+            thread_pool: None,
+            breed_parallel: None,
+            failure_policy: FailurePolicy::Abort,
+            non_finite_policy: NonFinitePolicy::Worst,
+
+            #[cfg(feature = "serde")]
+            autosave: None,
+            #[cfg(feature = "serde")]
+            checkpoint_error: None,
         }
     }
 
     //--------------------------------------------------------------------------
 
-    /// Sets the random seed of the population.
+    /// Sets the random seed of the population. Each generation draws from a
+    /// generator derived from this seed and the generation number, and each
+    /// child is bred with its own generator derived from that.
     pub fn set_rand_seed(&mut self, seed: usize) -> &mut Self {
         self.seed = seed;
         self
     }
 
-    /// Sets the maximum size of the population. If already populated with more
-    /// than this amount a random section of the population is killed.
+    /// Sets the maximum size (> 0) of the population. If already populated
+    /// with more than this amount a random section of the population is killed.
     pub fn set_size(&mut self, size: usize) -> &mut Self {
+        self.try_set_size(size).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Sets the maximum size of the population in the same way as `set_size`,
+    /// returning an error if it is invalid.
+    pub fn try_set_size(&mut self, size: usize) -> Result<&mut Self, PopulationError> {
+        if size == 0 {
+            return Err(PopulationError::InvalidSize(size));
+        }
         self.units.truncate(size);
         self.max_size = size;
-        self
+        Ok(self)
     }
 
     /// Sets the breed_factor (0 < b <= 1) of the genetic algorithm, which is
@@ -110,9 +169,18 @@ impl<T: Unit> Population<T> {
     /// breed, which will slow the algorithm down but allow it to escape local
     /// peaks.
     pub fn set_breed_factor(&mut self, breed_factor: f64) -> &mut Self {
-        assert!(breed_factor > 0.0 && breed_factor <= 1.0);
+        self.try_set_breed_factor(breed_factor)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Sets the breed_factor in the same way as `set_breed_factor`, returning
+    /// an error if it is invalid.
+    pub fn try_set_breed_factor(&mut self, breed_factor: f64) -> Result<&mut Self, PopulationError> {
+        if !(breed_factor > 0.0 && breed_factor <= 1.0) {
+            return Err(PopulationError::InvalidBreedFactor(breed_factor));
+        }
         self.breed_factor = breed_factor;
-        self
+        Ok(self)
     }
 
     /// Sets the survival_factor (0 <= b <= 1) of the genetic algorithm, which
@@ -128,8 +196,102 @@ impl<T: Unit> Population<T> {
     /// 0.5 * 0.9 * 100 = 45%
     ///
     pub fn set_survival_factor(&mut self, survival_factor: f64) -> &mut Self {
-        assert!(survival_factor >= 0.0 && survival_factor <= 1.0);
+        self.try_set_survival_factor(survival_factor)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Sets the survival_factor in the same way as `set_survival_factor`,
+    /// returning an error if it is invalid.
+    pub fn try_set_survival_factor(
+        &mut self,
+        survival_factor: f64,
+    ) -> Result<&mut Self, PopulationError> {
+        if !(0.0..=1.0).contains(&survival_factor) {
+            return Err(PopulationError::InvalidSurvivalFactor(survival_factor));
+        }
         self.survival_factor = survival_factor;
+        Ok(self)
+    }
+
+    /// Sets the number of elites, which are the strongest units of each
+    /// generation that are guaranteed to be carried into the next generation
+    /// unchanged, regardless of the survival factor. With one or more elites
+    /// the best fitness of the population never decreases.
+    ///
+    /// Elites count towards the survivors of an epoch, and so if the survival
+    /// factor already carries more units than this it has no effect. Defaults
+    /// to 0.
+    pub fn set_elitism(&mut self, elitism: usize) -> &mut Self {
+        self.elitism = elitism;
+        self
+    }
+
+    /// Sets the strategy used for choosing the parents of each child from the
+    /// breeding population. Defaults to `Truncation`, where each breeder mates
+    /// in turn with a random partner.
+    pub fn set_selection<S: Selection + 'static>(&mut self, selection: S) -> &mut Self {
+        self.selection = Box::new(selection);
+        self
+    }
+
+    /// Adds an observer that is notified after each generation has been
+    /// evaluated, whether by `epochs`, `epochs_parallel` or `step`. If any
+    /// observer returns `Control::Stop` the run ends with that generation.
+    pub fn add_observer<O: Observer<T> + 'static>(&mut self, observer: O) -> &mut Self {
+        self.observers.push(Box::new(observer));
+        self
+    }
+
+    /// Sets whether the population seeks units with the greatest or the least
+    /// fitness. Defaults to `Objective::Maximize`.
+    pub fn set_objective(&mut self, objective: Objective) -> &mut Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Sets the condition for ending a run before all epochs have been run,
+    /// which is checked after each generation is evaluated. Conditions can be
+    /// combined with `Termination::or` and `Termination::and`. By default
+    /// every epoch is run.
+    pub fn set_termination<C>(&mut self, termination: C) -> &mut Self
+    where
+        C: Termination<T::Fitness> + 'static,
+    {
+        self.termination = Some(Box::new(termination));
+        self
+    }
+
+    /// Sets a hall of fame, which archives the strongest distinct units ever
+    /// evaluated and can be obtained with `hall_of_fame` after a run.
+    pub fn set_hall_of_fame(&mut self, hall_of_fame: HallOfFame<T>) -> &mut Self {
+        self.hall_of_fame = Some(hall_of_fame);
+        self
+    }
+
+    /// Sets a thread pool on which the fitness of units is calculated by
+    /// `epochs`, `step` and `finish_ranked`, and on which children are bred if
+    /// parallel breeding is enabled with `set_parallel_breeding`. The pool is
+    /// shared rather than copied, and so the same threads can be reused by many
+    /// populations.
+    pub fn set_thread_pool(&mut self, thread_pool: &ThreadPool) -> &mut Self {
+        self.thread_pool = Some(thread_pool.clone());
+        self
+    }
+
+    /// Sets what happens to units whose fitness calculation returns an error
+    /// or panics. Defaults to `FailurePolicy::Abort`, where `try_epochs`,
+    /// `try_epochs_parallel` and `try_step` return the error and other methods
+    /// panic with it.
+    pub fn set_failure_policy(&mut self, failure_policy: FailurePolicy) -> &mut Self {
+        self.failure_policy = failure_policy;
+        self
+    }
+
+    /// Sets how units with a fitness of NaN or infinity are treated. Defaults
+    /// to `NonFinitePolicy::Worst`, where such units are ranked below every
+    /// unit with a finite fitness.
+    pub fn set_non_finite_policy(&mut self, non_finite_policy: NonFinitePolicy) -> &mut Self {
+        self.non_finite_policy = non_finite_policy;
         self
     }
 
@@ -138,190 +300,758 @@ impl<T: Unit> Population<T> {
     /// An epoch that allows units to breed and mutate without harsh culling.
     /// It's important to sometimes allow 'weak' units to produce generations
     /// that might escape local peaks in certain dimensions.
-    fn epoch(&self, units: &mut Vec<LazyUnit<T>>, mut rng: StdRng) -> StdRng {
-        assert!(units.len() > 0);
+    fn epoch(&mut self) {
+        assert!(!self.units.is_empty());
+
+        let seed: &[_] = &[self.seed, self.report.generations as usize];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let mut previous = mem::take(&mut self.units);
 
         // breed_factor dicates how large a percentage of the population will be
-        // able to breed.
-        let breed_up_to = (self.breed_factor * (units.len() as f64)) as usize;
-        let mut breeders: Vec<LazyUnit<T>> = Vec::new();
-
-        while let Some(unit) = units.pop() {
-            breeders.push(unit);
-            if breeders.len() == breed_up_to {
-                break;
+        // able to breed. Always at least one.
+        let breed_up_to = ((self.breed_factor * (previous.len() as f64)) as usize)
+            .max(1)
+            .min(previous.len());
+
+        // The strongest of our breeders will survive each epoch, and the elites
+        // survive regardless of the survival factor.
+        let surviving_parents = (breed_up_to as f64 * self.survival_factor).ceil() as usize;
+        let survivors = surviving_parents
+            .max(self.elitism)
+            .min(previous.len())
+            .min(self.max_size);
+
+        // Selection strategies are given fitness where greater is better, and
+        // fitness that is not a single number is replaced by its rank.
+        let breeders = &previous[..breed_up_to];
+        let objective = self.objective;
+        let fitnesses: Vec<f64> = breeders
+            .iter()
+            .map(|b| b.lazy_fitness.as_ref().and_then(Fitness::to_f64).map(|f| objective.orient(f)))
+            .collect::<Option<Vec<f64>>>()
+            .unwrap_or_else(|| (0..breed_up_to).map(|i| (breed_up_to - i) as f64).collect());
+        let mut offspring: Vec<Offspring<T>> = self.selection
+            .select(&fitnesses, self.max_size - survivors, &mut rng)
+            .into_iter()
+            .map(|parents| {
+                Offspring {
+                    parents,
+                    rng: rng.gen(),
+                    unit: None,
+                }
+            })
+            .collect();
+
+        // Each child is bred with its own generator, and so the outcome does
+        // not depend on whether children are bred in parallel.
+        match (self.breed_parallel, &self.thread_pool) {
+            (Some(breed_parallel), Some(thread_pool)) => {
+                breed_parallel(thread_pool, &previous, &mut offspring)
+            }
+            _ => {
+                for child in &mut offspring {
+                    child.breed(&previous);
+                }
             }
         }
-        units.clear();
-
-        // The strongest half of our breeders will survive each epoch. Always at
-        // least one.
-        let surviving_parents = (breeders.len() as f64 * self.survival_factor).ceil() as usize;
-
-        let pcnt_range = Range::new(0, breeders.len());
-        for i in 0..self.max_size - surviving_parents {
-            let rs = pcnt_range.ind_sample(&mut rng);
-            units.push(LazyUnit::from(
-                breeders[i % breeders.len()].unit.breed_with(
-                    &breeders[rs].unit,
-                ),
-            ));
-        }
+        self.units.extend(offspring.into_iter().map(|child| {
+            LazyUnit::from(child.unit.expect("child was not bred"))
+        }));
 
-        // Move our survivors into the new generation.
-        units.append(&mut breeders.drain(0..surviving_parents).collect());
+        // Move our survivors into the new generation along with their cached
+        // fitness.
+        previous.truncate(survivors);
+        self.units.extend(previous);
 
-        rng
+        self.report.generations += 1;
+        self.evaluated = false;
     }
 
-    /// Runs a number of epochs where fitness is calculated across n parallel
-    /// processes. This is useful when the fitness calcuation is an expensive
-    /// operation.
-    pub fn epochs_parallel(&mut self, n_epochs: u32, n_processes: u32) -> &mut Self {
-        scope(|scope| {
-            let cvar_pair = Arc::new((Mutex::new(0), Condvar::new()));
-
-            let (tx, rx) = sync_channel(0);
-            let process_queue = Arc::new(Mutex::new(rx));
-
-            let processed_stack = Arc::new(Mutex::new(Vec::new()));
-
-            for _ in 0..n_processes {
-                let cvar_pair_clone = cvar_pair.clone();
-                let processed_stack_clone = processed_stack.clone();
-                let process_queue_clone = process_queue.clone();
-
-                scope.spawn(move || {
-                    let &(ref lock, ref cvar) = &*cvar_pair_clone;
-                    loop {
-                        let mut l_unit: LazyUnit<T> =
-                            match process_queue_clone.lock().ok().unwrap().recv() {
-                                Ok(u) => u,
-                                Err(_) => return,
-                            };
-                        l_unit.fitness();
-                        processed_stack_clone.lock().ok().unwrap().push(l_unit);
-                        {
-                            let mut processed = lock.lock().unwrap();
-                            *processed += 1;
-                            cvar.notify_all();
-                        }
-                    }
-                });
-            }
+    /// Sorts evaluated units such that the strongest units are at the start,
+    /// and units with a fitness of NaN are at the end.
+    fn sort_units(&mut self) {
+        let objective = self.objective;
+        self.units.sort_by(|a, b| match (&a.lazy_fitness, &b.lazy_fitness) {
+            (Some(a), Some(b)) => objective.cmp(b, a),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+    }
 
-            let &(ref lock, ref cvar) = &*cvar_pair;
-            let mut active_stack = Vec::new();
+    /// Calculates the fitness of the current generation if it has not been
+    /// evaluated yet, records its stats and notifies observers. Returns the
+    /// reason for ending the run if any condition is met, or an error if the
+    /// fitness of units failed and the failure policy ends the run.
+    fn evaluate(&mut self) -> Result<Option<TerminationReason>, EvaluationError> {
+        let started = Instant::now();
+        let mut stopped = false;
 
-            while let Some(unit) = self.units.pop() {
-                active_stack.push(LazyUnit::from(unit));
+        if !self.evaluated {
+            let pending = self.units
+                .iter()
+                .filter(|u| u.lazy_fitness.is_none())
+                .count();
+            let attempts = match self.failure_policy {
+                FailurePolicy::Retry(retries) => retries.saturating_add(1),
+                _ => 1,
+            };
+            let failures = evaluator::evaluate(
+                &mut self.units,
+                self.thread_pool.as_ref(),
+                attempts,
+                self.non_finite_policy != NonFinitePolicy::Worst,
+            );
+            self.report.evaluations += pending;
+
+            if self.non_finite_policy == NonFinitePolicy::Panic {
+                for failure in &failures {
+                    if let Failure::NonFinite(fitness) = *failure {
+                        panic!(
+                            "fitness of a unit in generation {} is {}, which the non-finite \
+                             policy does not allow",
+                            self.report.generations,
+                            fitness
+                        );
+                    }
+                }
             }
 
-            let seed: &[_] = &[self.seed];
-            let mut rng: StdRng = SeedableRng::from_seed(seed);
+            if !failures.is_empty() {
+                self.handle_failures(failures)?;
+            }
+            self.evaluated = true;
 
-            for i in 0..(n_epochs + 1) {
-                let jobs_total = active_stack.len();
+            self.sort_units();
 
-                while let Some(unit) = active_stack.pop() {
-                    tx.send(unit).unwrap();
-                }
+            let fitnesses: Vec<T::Fitness> = self.units
+                .iter()
+                .filter_map(|u| u.lazy_fitness.clone())
+                .collect();
+            let stats = GenerationStats::from_fitnesses(
+                self.report.generations,
+                &fitnesses,
+                pending,
+                self.objective,
+            );
 
-                let mut jobs_processed = lock.lock().unwrap();
-                while *jobs_processed != jobs_total {
-                    jobs_processed = cvar.wait(jobs_processed).unwrap();
-                }
-                *jobs_processed = 0;
-
-                // Swap the full processed_stack with the active stack.
-                mem::swap(&mut active_stack, &mut processed_stack.lock().ok().unwrap());
-
-                // We want to sort such that highest fitness units are at the
-                // end.
-                active_stack.sort_by(|a, b| {
-                    a.lazy_fitness
-                        .unwrap_or(0.0)
-                        .partial_cmp(&b.lazy_fitness.unwrap_or(0.0))
-                        .unwrap_or(Ordering::Equal)
-                });
-
-                // If we have the perfect solution then break early.
-                if active_stack.last().unwrap().lazy_fitness.unwrap_or(0.0) == 1.0 {
-                    break;
+            {
+                let view = GenerationView::new(&self.units, &stats);
+                for observer in &mut self.observers {
+                    stopped |= observer.on_generation(&view) == Control::Stop;
                 }
+            }
+            self.report.history.push(stats);
 
-                if i != n_epochs {
-                    rng = self.epoch(&mut active_stack, rng);
+            if let Some(ref mut hall_of_fame) = self.hall_of_fame {
+                hall_of_fame.record(&self.units, self.report.generations, self.objective);
+                if hall_of_fame.reinject(&mut self.units, &self.report.history, self.objective) {
+                    self.sort_units();
                 }
             }
 
-            // Reverse the order of units such that the first unit is the
-            // strongest candidate.
-            while let Some(unit) = active_stack.pop() {
-                self.units.push(unit.unit);
-            }
-        });
+            #[cfg(feature = "serde")]
+            self.autosave();
+        }
 
-        self
+        self.report.elapsed += started.elapsed();
+
+        let reason = self.termination.as_ref().and_then(|termination| {
+            termination.check(&Progress {
+                history: &self.report.history,
+                evaluations: self.report.evaluations - self.run_evaluations,
+                elapsed: self.report.elapsed - self.run_elapsed,
+                objective: self.objective,
+            })
+        });
+        if reason.is_some() {
+            Ok(reason)
+        } else if stopped {
+            Ok(Some(TerminationReason::Stopped))
+        } else {
+            Ok(None)
+        }
     }
 
-    /// Runs a number of epochs on a single process.
-    pub fn epochs(&mut self, n_epochs: u32) -> &mut Self {
-        let mut processed_stack = Vec::new();
-        let mut active_stack = Vec::new();
+    /// Applies the failure policy to the units of the current generation that
+    /// failed to be evaluated, which are left without a fitness.
+    fn handle_failures(&mut self, mut failures: Vec<Failure>) -> Result<(), EvaluationError> {
+        let worst = {
+            let evaluated = self.units.iter().filter_map(|u| u.lazy_fitness.as_ref());
+            self.objective.worst(evaluated).cloned()
+        };
+        let any_evaluated = self.units.iter().any(|u| u.lazy_fitness.is_some());
 
-        while let Some(unit) = self.units.pop() {
-            active_stack.push(LazyUnit::from(unit));
+        match (self.failure_policy, worst) {
+            (FailurePolicy::WorstFitness, Some(worst)) => {
+                for unit in self.units.iter_mut().filter(|u| u.lazy_fitness.is_none()) {
+                    unit.lazy_fitness = Some(worst.clone());
+                }
+                Ok(())
+            }
+            (FailurePolicy::Discard, _) if any_evaluated => {
+                self.units.retain(|u| u.lazy_fitness.is_some());
+                Ok(())
+            }
+            // Either the policy ends the run, or every unit failed and there is
+            // nothing left to carry on with.
+            _ => Err(EvaluationError {
+                generation: self.report.generations,
+                failed: failures.len(),
+                failure: failures.remove(0),
+            }),
         }
+    }
 
-        let seed: &[_] = &[self.seed];
-        let mut rng: StdRng = SeedableRng::from_seed(seed);
+    /// Evaluates the current generation, panicking if the failure policy ends
+    /// the run.
+    fn evaluate_or_panic(&mut self) -> Option<TerminationReason> {
+        self.evaluate().unwrap_or_else(|err| panic!("{}", err))
+    }
 
-        for i in 0..(n_epochs + 1) {
-            while let Some(mut unit) = active_stack.pop() {
-                unit.fitness();
-                processed_stack.push(unit);
-            }
+    /// Runs up to `n_epochs` epochs.
+    fn run(&mut self, n_epochs: u32) -> Result<(), PopulationError> {
+        if self.units.is_empty() {
+            return Err(PopulationError::EmptyPopulation);
+        }
+        self.report.termination = None;
+        self.start_run();
 
-            // Swap the full processed_stack with the active stack.
-            mem::swap(&mut active_stack, &mut processed_stack);
-
-            // We want to sort such that highest fitness units are at the
-            // end.
-            active_stack.sort_by(|a, b| {
-                a.lazy_fitness
-                    .unwrap_or(0.0)
-                    .partial_cmp(&b.lazy_fitness.unwrap_or(0.0))
-                    .unwrap_or(Ordering::Equal)
-            });
-
-            // If we have the perfect solution then break early.
-            if active_stack.last().unwrap().lazy_fitness.unwrap_or(0.0) == 1.0 {
-                break;
+        for i in 0..(n_epochs + 1) {
+            if let Some(reason) = self.evaluate()? {
+                self.report.termination = Some(reason);
+                return Ok(());
             }
 
             if i != n_epochs {
-                rng = self.epoch(&mut active_stack, rng);
+                let started = Instant::now();
+                self.epoch();
+                self.report.elapsed += started.elapsed();
             }
         }
 
-        // Reverse the order of units such that the first unit is the
-        // strongest candidate.
-        while let Some(unit) = active_stack.pop() {
-            self.units.push(unit.unit);
+        self.report.termination = Some(TerminationReason::Completed);
+        Ok(())
+    }
+
+    /// Runs a number of epochs where fitness is calculated across n parallel
+    /// processes. This is useful when the fitness calcuation is an expensive
+    /// operation.
+    ///
+    /// This spawns a new thread pool for the duration of the call. When running
+    /// many populations, create a `ThreadPool` once and pass it to each with
+    /// `set_thread_pool` instead.
+    ///
+    /// Panics if the number of processes is zero, if the population is empty,
+    /// or if the fitness of units fails and the failure policy ends the run,
+    /// see `try_epochs_parallel`.
+    pub fn epochs_parallel(&mut self, n_epochs: u32, n_processes: u32) -> &mut Self {
+        self.try_epochs_parallel(n_epochs, n_processes)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs a number of epochs in the same way as `epochs_parallel`, returning
+    /// an error if the number of processes is zero, if the population is empty,
+    /// or if the fitness of units fails and the failure policy ends the run.
+    pub fn try_epochs_parallel(
+        &mut self,
+        n_epochs: u32,
+        n_processes: u32,
+    ) -> Result<&mut Self, PopulationError> {
+        if n_processes == 0 {
+            return Err(PopulationError::InvalidThreads(n_processes));
         }
+        let thread_pool = ThreadPool::new(n_processes as usize);
+        let previous = self.thread_pool.replace(thread_pool);
+        let result = self.run(n_epochs);
+        self.thread_pool = previous;
+        result.map(move |_| self)
+    }
 
-        self
+    /// Runs a number of epochs, calculating fitness on the thread pool set with
+    /// `set_thread_pool` if any, otherwise on a single process.
+    ///
+    /// Panics if the population is empty, or if the fitness of units fails and
+    /// the failure policy ends the run, see `try_epochs`.
+    pub fn epochs(&mut self, n_epochs: u32) -> &mut Self {
+        self.try_epochs(n_epochs).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Runs a number of epochs in the same way as `epochs`, returning an error
+    /// if the population is empty, or if the fitness of units fails and the
+    /// failure policy ends the run.
+    pub fn try_epochs(&mut self, n_epochs: u32) -> Result<&mut Self, PopulationError> {
+        self.run(n_epochs)?;
+        Ok(self)
+    }
+
+    /// Advances the population by exactly one generation, evaluating the
+    /// current generation first if needed, and returns a view of the new
+    /// generation. Fitnesses already calculated are kept, and so steps can be
+    /// freely mixed with calls to `epochs`.
+    ///
+    /// Observers are notified of the new generation, and the reason for ending
+    /// the run is recorded in the report if a termination condition is met or
+    /// an observer requests a stop, but the caller is free to keep stepping.
+    /// Each step counts as a run of its own for the elapsed time and
+    /// evaluations seen by termination conditions.
+    ///
+    /// Panics if the population is empty, or if the fitness of units fails and
+    /// the failure policy ends the run, see `try_step`.
+    pub fn step(&mut self) -> GenerationView<'_, T> {
+        self.try_step().unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Advances the population by one generation in the same way as `step`,
+    /// returning an error if the population is empty, or if the fitness of
+    /// units fails and the failure policy ends the run.
+    pub fn try_step(&mut self) -> Result<GenerationView<'_, T>, PopulationError> {
+        self.start_run();
+        self.advance()?;
+        Ok(GenerationView::new(&self.units, self.report.history.last().unwrap()))
+    }
+
+    /// Advances the population by one generation as part of the current run.
+    fn advance(&mut self) -> Result<(), PopulationError> {
+        if self.units.is_empty() {
+            return Err(PopulationError::EmptyPopulation);
+        }
+        self.report.termination = None;
+
+        self.evaluate()?;
+        let started = Instant::now();
+        self.epoch();
+        self.report.elapsed += started.elapsed();
+        self.report.termination = self.evaluate()?;
+        Ok(())
+    }
+
+    /// Marks the start of a run, from which the elapsed time and evaluations
+    /// seen by termination conditions are counted.
+    fn start_run(&mut self) {
+        self.run_elapsed = self.report.elapsed;
+        self.run_evaluations = self.report.evaluations;
+    }
+
+    /// Returns an iterator that advances the population by one generation for
+    /// each item, yielding a snapshot of each new generation. The iterator
+    /// ends after the generation where a termination condition is met or an
+    /// observer requests a stop. The iterator counts as a single run for the
+    /// elapsed time and evaluations seen by termination conditions.
+    pub fn generations(&mut self) -> Generations<'_, T> {
+        self.start_run();
+        Generations {
+            population: self,
+            done: false,
+        }
+    }
+
+    /// Returns a report of the evolution of the population so far, containing
+    /// the stats of each generation and why the most recent run ended. Returns
+    /// `None` if no generation has been evaluated yet.
+    pub fn report(&self) -> Option<&EvolutionReport<T::Fitness>> {
+        if self.report.history.is_empty() {
+            None
+        } else {
+            Some(&self.report)
+        }
     }
 
     //--------------------------------------------------------------------------
 
+    /// Returns the hall of fame set with `set_hall_of_fame`, containing the
+    /// strongest distinct units evaluated so far.
+    pub fn hall_of_fame(&self) -> Option<&HallOfFame<T>> {
+        self.hall_of_fame.as_ref()
+    }
+
+    /// Returns the strongest unit of the population, or `None` if the current
+    /// generation has not been evaluated yet.
+    pub fn best(&self) -> Option<&T> {
+        if self.evaluated {
+            self.units.first().map(|u| &u.unit)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the fitness of the strongest unit of the population, or `None`
+    /// if the current generation has not been evaluated yet.
+    pub fn best_fitness(&self) -> Option<T::Fitness> {
+        if self.evaluated {
+            self.units.first().and_then(|u| u.lazy_fitness.clone())
+        } else {
+            None
+        }
+    }
+
     /// Returns the full population of units, ordered such that the first
     /// element is the strongest candidate. This collection can be used to
     /// create a new population.
     pub fn finish(&mut self) -> Vec<T> {
-        let mut empty_units: Vec<T> = Vec::new();
+        let mut empty_units = Vec::new();
         mem::swap(&mut empty_units, &mut self.units);
+        self.evaluated = false;
+        empty_units.into_iter().map(|u| u.unit).collect()
+    }
+
+    /// Returns the full population of units along with their fitness, ordered
+    /// such that the first element is the strongest candidate. Fitnesses that
+    /// were already calculated are reused, and the current generation is
+    /// evaluated first if needed.
+    pub fn finish_ranked(&mut self) -> Vec<Ranked<T>> {
+        if !self.units.is_empty() {
+            self.evaluate_or_panic();
+        }
+
+        let mut empty_units = Vec::new();
+        mem::swap(&mut empty_units, &mut self.units);
+        self.evaluated = false;
         empty_units
+            .into_iter()
+            .filter_map(|LazyUnit { unit, lazy_fitness }| {
+                lazy_fitness.map(|fitness| Ranked { unit, fitness })
+            })
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Breeds each child across the threads of a pool, which needs `T: Sync`. It is
+/// captured by `set_parallel_breeding` in the same way as the functions of a
+/// `HallOfFame`.
+type BreedParallel<T> = fn(&ThreadPool, &[LazyUnit<T>], &mut [Offspring<T>]);
+
+/// A child of an epoch, along with its parents and the generator it is bred
+/// with.
+struct Offspring<T: Unit> {
+    parents: (usize, usize),
+    rng: XorShiftRng,
+    unit: Option<T>,
+}
+
+impl<T: Unit> Offspring<T> {
+    fn breed(&mut self, previous: &[LazyUnit<T>]) {
+        let (a, b) = self.parents;
+        self.unit = Some(previous[a].unit.breed_with_rng(&previous[b].unit, &mut self.rng));
+    }
+}
+
+impl<T: Unit + Sync> Population<T> {
+    /// Sets whether children are bred across the threads of the thread pool
+    /// used for calculating fitness, which is useful when breeding is an
+    /// expensive operation. Each child is bred with its own generator, and so
+    /// the outcome for a given seed is the same either way. Defaults to false.
+    pub fn set_parallel_breeding(&mut self, parallel: bool) -> &mut Self {
+        self.breed_parallel = if parallel {
+            Some(|thread_pool, previous, offspring| {
+                thread_pool.for_each(offspring, |child| child.breed(previous))
+            })
+        } else {
+            None
+        };
+        self
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Periodic checkpointing configured with `Population::set_checkpoint`. The
+/// save function needs `T: Serialize`, and so is captured by `set_checkpoint`
+/// in the same way as the functions of a `HallOfFame`.
+#[cfg(feature = "serde")]
+struct Autosave<T: Unit> {
+    path: PathBuf,
+    n_generations: u32,
+    save: fn(&Population<T>, &Path) -> Result<(), CheckpointError>,
+}
+
+/// The state of a population written to a checkpoint.
+#[cfg(feature = "serde")]
+#[derive(Serialize)]
+#[serde(bound = "T: ::serde::Serialize, T::Fitness: ::serde::Serialize")]
+struct CheckpointRef<'a, T: Unit + 'a> {
+    units: &'a [LazyUnit<T>],
+    evaluated: bool,
+    seed: usize,
+    breed_factor: f64,
+    survival_factor: f64,
+    max_size: usize,
+    elitism: usize,
+    objective: Objective,
+    report: &'a EvolutionReport<T::Fitness>,
+}
+
+/// The state of a population read from a checkpoint.
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+#[serde(bound = "T: ::serde::Deserialize<'de>, T::Fitness: ::serde::Deserialize<'de>")]
+struct Checkpoint<T: Unit> {
+    units: Vec<LazyUnit<T>>,
+    evaluated: bool,
+    seed: usize,
+    breed_factor: f64,
+    survival_factor: f64,
+    max_size: usize,
+    elitism: usize,
+    objective: Objective,
+    report: EvolutionReport<T::Fitness>,
+}
+
+#[cfg(feature = "serde")]
+impl<T: Unit> Population<T> {
+    /// Writes a checkpoint if periodic checkpointing is due for the current
+    /// generation.
+    fn autosave(&mut self) {
+        let result = match self.autosave {
+            Some(ref autosave) if self.report.generations.is_multiple_of(autosave.n_generations) => {
+                (autosave.save)(self, &autosave.path)
+            }
+            _ => return,
+        };
+        if let Err(err) = result {
+            self.checkpoint_error = Some(err);
+        }
+    }
+
+    /// Returns the error of the most recent periodic checkpoint that failed, if
+    /// any. A failed checkpoint does not interrupt a run.
+    pub fn checkpoint_error(&self) -> Option<&CheckpointError> {
+        self.checkpoint_error.as_ref()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Population<T>
+where
+    T: Unit + Serialize,
+    T::Fitness: Serialize,
+{
+    /// Saves the population to a checkpoint file at `path`, which can be
+    /// restored with `Population::load_checkpoint`.
+    pub fn save_checkpoint<P: AsRef<Path>>(&self, path: P) -> Result<(), CheckpointError> {
+        checkpoint::write_atomic(
+            path.as_ref(),
+            &CheckpointRef {
+                units: &self.units,
+                evaluated: self.evaluated,
+                seed: self.seed,
+                breed_factor: self.breed_factor,
+                survival_factor: self.survival_factor,
+                max_size: self.max_size,
+                elitism: self.elitism,
+                objective: self.objective,
+                report: &self.report,
+            },
+        )
+    }
+
+    /// Enables periodic checkpointing, where the population is saved to `path`
+    /// after every `n_generations` (> 0) generations are evaluated by
+    /// `epochs`, `epochs_parallel` or `step`. Each checkpoint replaces the
+    /// last.
+    pub fn set_checkpoint<P: AsRef<Path>>(&mut self, path: P, n_generations: u32) -> &mut Self {
+        self.try_set_checkpoint(path, n_generations)
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Enables periodic checkpointing in the same way as `set_checkpoint`,
+    /// returning an error if the number of generations is zero.
+    pub fn try_set_checkpoint<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        n_generations: u32,
+    ) -> Result<&mut Self, PopulationError> {
+        if n_generations == 0 {
+            return Err(PopulationError::InvalidCheckpointInterval(n_generations));
+        }
+        self.autosave = Some(Autosave {
+            path: path.as_ref().to_path_buf(),
+            n_generations,
+            save: |population, path| population.save_checkpoint(path),
+        });
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> Population<T>
+where
+    T: Unit + DeserializeOwned,
+    T::Fitness: DeserializeOwned,
+{
+    /// Restores a population from a checkpoint file at `path`. Selection
+    /// strategies, termination conditions, observers, the hall of fame, the
+    /// thread pool, parallel breeding, the failure and non-finite policies and
+    /// periodic checkpointing are not saved, and must be set again.
+    pub fn load_checkpoint<P: AsRef<Path>>(path: P) -> Result<Self, CheckpointError> {
+        let checkpoint: Checkpoint<T> = checkpoint::read(path.as_ref())?;
+
+        let mut population = Population::new(Vec::new());
+        population.units = checkpoint.units;
+        population.evaluated = checkpoint.evaluated;
+        population.seed = checkpoint.seed;
+        population.breed_factor = checkpoint.breed_factor;
+        population.survival_factor = checkpoint.survival_factor;
+        population.max_size = checkpoint.max_size;
+        population.elitism = checkpoint.elitism;
+        population.objective = checkpoint.objective;
+        population.report = checkpoint.report;
+        Ok(population)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// An error caused by an invalid configuration of a population, or by a run
+/// that could not be completed.
+#[derive(Debug)]
+pub enum PopulationError {
+    /// The breed factor is not within (0, 1].
+    InvalidBreedFactor(f64),
+
+    /// The survival factor is not within [0, 1].
+    InvalidSurvivalFactor(f64),
+
+    /// The size of the population is zero.
+    InvalidSize(usize),
+
+    /// The number of parallel processes is zero.
+    InvalidThreads(u32),
+
+    /// The number of generations between checkpoints is zero.
+    InvalidCheckpointInterval(u32),
+
+    /// The population has no units to run epochs on.
+    EmptyPopulation,
+
+    /// The SPEA2 archive size of a `MultiObjectivePopulation` is zero.
+    InvalidArchiveSize(usize),
+
+    /// The MOEA/D neighbourhood of a `MultiObjectivePopulation` is not within
+    /// (1, size].
+    InvalidNeighbourhood {
+        /// The number of subproblems in each neighbourhood.
+        neighbourhood: usize,
+        /// The size of the population.
+        size: usize,
+    },
+
+    /// A unit of a `MultiObjectivePopulation` has a different number of
+    /// objectives than were set, or than the other units have.
+    ObjectiveCountMismatch {
+        /// The number of objectives expected.
+        expected: usize,
+        /// The number of objectives the unit has.
+        found: usize,
+    },
+
+    /// The fitness of units failed and the failure policy ended the run.
+    Evaluation(EvaluationError),
+}
+
+impl fmt::Display for PopulationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PopulationError::InvalidBreedFactor(factor) => {
+                write!(f, "breed factor must be within (0, 1], got {}", factor)
+            }
+            PopulationError::InvalidSurvivalFactor(factor) => {
+                write!(f, "survival factor must be within [0, 1], got {}", factor)
+            }
+            PopulationError::InvalidSize(size) => {
+                write!(f, "population size must be greater than 0, got {}", size)
+            }
+            PopulationError::InvalidThreads(n) => {
+                write!(f, "number of processes must be greater than 0, got {}", n)
+            }
+            PopulationError::InvalidCheckpointInterval(n) => {
+                write!(f, "checkpoint interval must be greater than 0, got {}", n)
+            }
+            PopulationError::EmptyPopulation => write!(f, "population has no units"),
+            PopulationError::InvalidArchiveSize(size) => {
+                write!(f, "archive size must be greater than 0, got {}", size)
+            }
+            PopulationError::InvalidNeighbourhood { neighbourhood, size } => {
+                write!(f, "neighbourhood must be within (1, {}], got {}", size, neighbourhood)
+            }
+            PopulationError::ObjectiveCountMismatch { expected, found } => {
+                write!(f, "units must have {} objectives, got {}", expected, found)
+            }
+            PopulationError::Evaluation(ref err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for PopulationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            PopulationError::Evaluation(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<EvaluationError> for PopulationError {
+    fn from(err: EvaluationError) -> Self {
+        PopulationError::Evaluation(err)
+    }
+}
+
+/// A unit paired with its fitness, as returned by `Population::finish_ranked`.
+#[derive(Clone, Debug)]
+pub struct Ranked<T: Unit> {
+    /// The unit.
+    pub unit: T,
+
+    /// The fitness of the unit.
+    pub fitness: T::Fitness,
+}
+
+//------------------------------------------------------------------------------
+
+/// A snapshot of a generation yielded by `Generations`.
+#[derive(Clone, Debug)]
+pub struct Snapshot<T: Unit> {
+    /// The strongest unit of the generation.
+    pub best: T,
+
+    /// The fitness of the strongest unit.
+    pub fitness: T::Fitness,
+
+    /// The fitness stats of the generation.
+    pub stats: GenerationStats<T::Fitness>,
+}
+
+/// An iterator that advances a population by one generation per item, created
+/// with `Population::generations`.
+pub struct Generations<'a, T: Unit + 'a> {
+    population: &'a mut Population<T>,
+    done: bool,
+}
+
+impl<'a, T: Unit + Clone + 'a> Iterator for Generations<'a, T> {
+    type Item = Snapshot<T>;
+
+    fn next(&mut self) -> Option<Snapshot<T>> {
+        if self.done {
+            return None;
+        }
+
+        self.population.advance().unwrap_or_else(|err| panic!("{}", err));
+        let snapshot = {
+            let population = &*self.population;
+            let stats = population.report.history.last().unwrap();
+            let view = GenerationView::new(&population.units, stats);
+            let (best, fitness) = view.best().unwrap();
+            Snapshot {
+                best: best.clone(),
+                fitness: fitness.clone(),
+                stats: view.stats().clone(),
+            }
+        };
+        self.done = self.population.report.termination.is_some();
+
+        Some(snapshot)
     }
 }
@@ -0,0 +1,163 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use fitness::{Fitness, Objective};
+
+use std::time::Duration;
+
+/// Summarises the fitness of a single generation of units, recorded once the
+/// generation has been evaluated.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GenerationStats<F = f64> {
+    /// The generation number, where 0 is the initial population.
+    pub generation: u32,
+
+    /// The fitness of the strongest unit of the generation, which is the
+    /// highest fitness when maximizing and the lowest when minimizing.
+    pub best: F,
+
+    /// The mean fitness of the generation, or `None` if the fitness is not a
+    /// single number.
+    pub mean: Option<f64>,
+
+    /// The fitness of the weakest unit of the generation, ignoring units with
+    /// a fitness of NaN.
+    pub worst: F,
+
+    /// The standard deviation of fitness across the generation, or `None` if
+    /// the fitness is not a single number.
+    pub std_dev: Option<f64>,
+
+    /// The number of fitness evaluations performed for this generation. Units
+    /// that survive from a previous generation are not evaluated again.
+    pub evaluations: usize,
+}
+
+impl<F: Fitness> GenerationStats<F> {
+    /// Calculates the stats of a generation from the fitness of each unit.
+    /// Panics if `fitnesses` is empty.
+    pub fn from_fitnesses(
+        generation: u32,
+        fitnesses: &[F],
+        evaluations: usize,
+        objective: Objective,
+    ) -> Self {
+        let best = objective.best(fitnesses).expect("no fitnesses").clone();
+        let worst = objective.worst(fitnesses).unwrap_or(&best).clone();
+
+        let numbers = fitnesses
+            .iter()
+            .map(Fitness::to_f64)
+            .collect::<Option<Vec<f64>>>();
+        let (mean, std_dev) = match numbers {
+            Some(numbers) => {
+                let n = numbers.len() as f64;
+                let mean = numbers.iter().sum::<f64>() / n;
+                let variance = numbers.iter().map(|f| (f - mean).powi(2)).sum::<f64>() / n;
+                (Some(mean), Some(variance.sqrt()))
+            }
+            None => (None, None),
+        };
+
+        GenerationStats {
+            generation,
+            best,
+            mean,
+            worst,
+            std_dev,
+            evaluations,
+        }
+    }
+}
+
+/// The reason a run of epochs came to an end.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TerminationReason {
+    /// All requested epochs were run.
+    Completed,
+
+    /// A unit reached the target fitness.
+    TargetReached,
+
+    /// The maximum number of generations were bred.
+    MaxGenerations,
+
+    /// The maximum number of fitness evaluations were performed.
+    MaxEvaluations,
+
+    /// The run exceeded its time limit.
+    TimeLimit,
+
+    /// The best fitness stopped improving.
+    Stagnated,
+
+    /// The fitness of the population converged.
+    Converged,
+
+    /// Every one of a combination of conditions was met, with the reason of
+    /// each.
+    All(Vec<TerminationReason>),
+
+    /// An observer requested that the run stop.
+    Stopped,
+}
+
+/// A record of the evolution of a population, which can be obtained from the
+/// population at any point.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EvolutionReport<F = f64> {
+    /// The stats of each generation in the order they were evaluated.
+    pub history: Vec<GenerationStats<F>>,
+
+    /// The number of new generations that were bred.
+    pub generations: u32,
+
+    /// The total number of fitness evaluations performed.
+    pub evaluations: usize,
+
+    /// The total wall-clock time spent breeding and evaluating units.
+    pub elapsed: Duration,
+
+    /// Why the most recent run ended, or `None` if the population is being
+    /// advanced step by step and no condition has been met.
+    pub termination: Option<TerminationReason>,
+}
+
+impl<F> Default for EvolutionReport<F> {
+    fn default() -> Self {
+        EvolutionReport {
+            history: Vec::new(),
+            generations: 0,
+            evaluations: 0,
+            elapsed: Duration::default(),
+            termination: None,
+        }
+    }
+}
+
+impl<F> EvolutionReport<F> {
+    /// Returns the stats of the final generation of the run.
+    pub fn last(&self) -> Option<&GenerationStats<F>> {
+        self.history.last()
+    }
+}
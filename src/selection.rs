@@ -0,0 +1,282 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use rand::{Rng, StdRng};
+use rand::distributions::{IndependentSample, Range};
+
+/// Selection is a strategy for choosing which units of the breeding population
+/// become the parents of each child in the next generation.
+pub trait Selection: Send + Sync {
+    /// Chooses the parents of `n_children` offspring. `fitnesses` contains the
+    /// fitness of each unit of the breeding population ordered from strongest
+    /// to weakest, and a pair of indexes into that slice must be returned for
+    /// each child.
+    ///
+    /// Fitness is always given on a scale where greater is better, and so is
+    /// negated when the population minimizes fitness. When the fitness of units
+    /// is not a single number, such as a tuple, each unit is given a score
+    /// from its rank instead, where the weakest unit scores 1.
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)>;
+}
+
+//------------------------------------------------------------------------------
+
+/// Truncation selection breeds each unit of the breeding population in turn
+/// with a mate chosen uniformly at random. Since the breeding population is
+/// already the strongest `breed_factor` of the population this is the default
+/// strategy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Truncation;
+
+impl Selection for Truncation {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let range = Range::new(0, fitnesses.len());
+        (0..n_children)
+            .map(|i| (i % fitnesses.len(), range.ind_sample(rng)))
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Tournament selection picks each parent as the strongest of `size` units
+/// drawn uniformly at random. Larger tournaments increase the selection
+/// pressure.
+#[derive(Clone, Copy, Debug)]
+pub struct Tournament {
+    size: usize,
+}
+
+impl Tournament {
+    /// Creates a tournament selection with `size` (> 0) contestants.
+    pub fn new(size: usize) -> Self {
+        assert!(size > 0);
+        Tournament { size }
+    }
+
+    fn pick(&self, n_units: usize, rng: &mut StdRng) -> usize {
+        let range = Range::new(0, n_units);
+
+        // Units are ordered strongest first, so the lowest index wins.
+        (0..self.size).map(|_| range.ind_sample(rng)).min().unwrap()
+    }
+}
+
+impl Selection for Tournament {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        (0..n_children)
+            .map(|_| {
+                (self.pick(fitnesses.len(), rng), self.pick(fitnesses.len(), rng))
+            })
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Roulette wheel selection picks each parent with a probability proportional
+/// to its fitness. If any fitness is negative all fitnesses are shifted such
+/// that the weakest unit has a weight of zero.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RouletteWheel;
+
+impl Selection for RouletteWheel {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let wheel = Wheel::new(proportional_weights(fitnesses));
+        (0..n_children)
+            .map(|_| (wheel.spin(rng.gen()), wheel.spin(rng.gen())))
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Stochastic universal sampling is a fitness proportional selection that uses
+/// evenly spaced pointers on a single spin of the wheel, which avoids the bias
+/// of repeatedly spinning a roulette wheel. Weights are calculated the same way
+/// as `RouletteWheel`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct StochasticUniversalSampling;
+
+impl Selection for StochasticUniversalSampling {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let wheel = Wheel::new(proportional_weights(fitnesses));
+        let n_parents = n_children * 2;
+        let start: f64 = rng.gen();
+
+        let mut parents: Vec<usize> = (0..n_parents)
+            .map(|i| wheel.spin((start + i as f64) / n_parents as f64))
+            .collect();
+
+        // Pointers are picked in order, so shuffle before pairing them up.
+        rng.shuffle(&mut parents);
+        parents.chunks(2).map(|p| (p[0], p[1])).collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Rank selection picks each parent with a probability derived from its rank
+/// within the breeding population rather than its raw fitness, which keeps the
+/// selection pressure constant regardless of the fitness scale.
+#[derive(Clone, Copy, Debug)]
+pub enum Rank {
+    /// Weights decrease linearly from the strongest to the weakest unit. The
+    /// value (1 <= p <= 2) is the expected number of children of the strongest
+    /// unit relative to the average.
+    Linear(f64),
+
+    /// Weights decrease exponentially with rank, where the value (0 < b <= 1)
+    /// is the base. The strongest unit has a weight of 1, the next b, then b^2
+    /// and so on.
+    Exponential(f64),
+}
+
+impl Rank {
+    /// Creates a linear rank selection with a selection pressure (1 <= p <= 2).
+    pub fn linear(pressure: f64) -> Self {
+        assert!((1.0..=2.0).contains(&pressure));
+        Rank::Linear(pressure)
+    }
+
+    /// Creates an exponential rank selection with a base (0 < b <= 1).
+    pub fn exponential(base: f64) -> Self {
+        assert!(base > 0.0 && base <= 1.0);
+        Rank::Exponential(base)
+    }
+}
+
+impl Selection for Rank {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        let n = fitnesses.len();
+        let weights = match *self {
+            Rank::Linear(pressure) => {
+                (0..n)
+                    .map(|i| if n == 1 {
+                        1.0
+                    } else {
+                        let rank = (n - 1 - i) as f64;
+                        (2.0 - pressure) + 2.0 * (pressure - 1.0) * rank / (n - 1) as f64
+                    })
+                    .collect()
+            }
+            Rank::Exponential(base) => (0..n).map(|i| base.powi(i as i32)).collect(),
+        };
+
+        let wheel = Wheel::new(weights);
+        (0..n_children)
+            .map(|_| (wheel.spin(rng.gen()), wheel.spin(rng.gen())))
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Boltzmann selection picks each parent with a probability proportional to
+/// `exp(fitness / temperature)`. High temperatures approach uniform selection
+/// whereas low temperatures strongly favour the fittest units.
+#[derive(Clone, Copy, Debug)]
+pub struct Boltzmann {
+    temperature: f64,
+}
+
+impl Boltzmann {
+    /// Creates a Boltzmann selection with a temperature (t > 0).
+    pub fn new(temperature: f64) -> Self {
+        assert!(temperature > 0.0);
+        Boltzmann { temperature }
+    }
+}
+
+impl Selection for Boltzmann {
+    fn select(&self, fitnesses: &[f64], n_children: usize, rng: &mut StdRng) -> Vec<(usize, usize)> {
+        // Subtracting the maximum fitness keeps the exponent from overflowing
+        // without changing the relative weights.
+        let max = fitnesses
+            .iter()
+            .cloned()
+            .filter(|f| f.is_finite())
+            .fold(f64::MIN, f64::max);
+        let wheel = Wheel::new(
+            fitnesses
+                .iter()
+                .map(|f| ((f - max) / self.temperature).exp())
+                .collect(),
+        );
+        (0..n_children)
+            .map(|_| (wheel.spin(rng.gen()), wheel.spin(rng.gen())))
+            .collect()
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Returns fitnesses as non-negative weights, shifting them such that the
+/// weakest unit with a finite fitness has a weight of zero if any are negative.
+fn proportional_weights(fitnesses: &[f64]) -> Vec<f64> {
+    let min = fitnesses
+        .iter()
+        .cloned()
+        .filter(|f| f.is_finite())
+        .fold(f64::MAX, f64::min);
+    let shift = if min < 0.0 { min } else { 0.0 };
+    fitnesses.iter().map(|f| f - shift).collect()
+}
+
+/// A wheel of cumulative weights for picking indexes proportionally.
+struct Wheel {
+    cumulative: Vec<f64>,
+}
+
+impl Wheel {
+    /// Creates a wheel where negative and NaN weights are never picked, and an
+    /// infinite weight outweighs every finite one.
+    fn new(weights: Vec<f64>) -> Self {
+        let infinite = weights.contains(&f64::INFINITY);
+        let mut total = 0.0;
+        let cumulative = weights
+            .into_iter()
+            .map(|w| {
+                total += if infinite {
+                    if w == f64::INFINITY { 1.0 } else { 0.0 }
+                } else if w > 0.0 {
+                    w
+                } else {
+                    0.0
+                };
+                total
+            })
+            .collect();
+        Wheel { cumulative }
+    }
+
+    /// Returns the index at a position (0 <= p < 1) of the wheel. If all
+    /// weights are zero the position is mapped uniformly onto the indexes.
+    fn spin(&self, position: f64) -> usize {
+        let n = self.cumulative.len();
+        let total = self.cumulative[n - 1];
+        if total <= 0.0 || !total.is_finite() {
+            return ((position * n as f64) as usize).min(n - 1);
+        }
+
+        let target = position * total;
+        self.cumulative.partition_point(|&c| c <= target).min(n - 1)
+    }
+}
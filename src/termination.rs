@@ -0,0 +1,305 @@
+// Copyright (c) 2017 Ashley Jeffs
+//
+// Permission is hereby granted, free of charge, to any person obtaining a copy
+// of this software and associated documentation files (the "Software"), to deal
+// in the Software without restriction, including without limitation the rights
+// to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+// copies of the Software, and to permit persons to whom the Software is
+// furnished to do so, subject to the following conditions:
+//
+// The above copyright notice and this permission notice shall be included in
+// all copies or substantial portions of the Software.
+//
+// THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+// IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+// FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+// AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+// LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+// OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
+// THE SOFTWARE.
+
+use report::{GenerationStats, TerminationReason};
+use fitness::{Fitness, Objective};
+
+use std::time::Duration;
+
+/// The progress of a run of epochs, used for deciding whether it should end.
+/// A run is a single call to `Population::epochs` or `Population::step`, or
+/// the lifetime of the iterator returned by `Population::generations`.
+pub struct Progress<'a, F: 'a = f64> {
+    /// The stats of each generation so far, the last being the most recently
+    /// evaluated generation.
+    pub history: &'a [GenerationStats<F>],
+
+    /// The number of fitness evaluations performed since the run started.
+    pub evaluations: usize,
+
+    /// The wall-clock time elapsed since the run started.
+    pub elapsed: Duration,
+
+    /// Whether the population maximizes or minimizes fitness.
+    pub objective: Objective,
+}
+
+impl<'a, F> Progress<'a, F> {
+    /// Returns the stats of the most recently evaluated generation.
+    pub fn current(&self) -> &GenerationStats<F> {
+        self.history.last().unwrap()
+    }
+}
+
+/// Termination is a condition for ending a run of epochs early, checked after
+/// each generation is evaluated. Conditions are generic over the fitness type
+/// of the population, where those that do not inspect fitness apply to any.
+pub trait Termination<F = f64>: Send {
+    /// Returns the reason for ending the run if the condition is met.
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason>;
+
+    /// Combines this condition with another, ending the run when either is
+    /// met.
+    fn or<C: Termination<F> + 'static>(self, other: C) -> Any<F>
+    where
+        Self: Sized + 'static,
+    {
+        Any::new(vec![Box::new(self), Box::new(other)])
+    }
+
+    /// Combines this condition with another, ending the run only when both are
+    /// met.
+    fn and<C: Termination<F> + 'static>(self, other: C) -> All<F>
+    where
+        Self: Sized + 'static,
+    {
+        All::new(vec![Box::new(self), Box::new(other)])
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once the best fitness of a generation reaches a target.
+#[derive(Clone, Copy, Debug)]
+pub struct TargetFitness<F = f64> {
+    target: F,
+}
+
+impl<F: Fitness> TargetFitness<F> {
+    /// Creates a condition met when the best fitness is at least `target`, or
+    /// at most `target` when minimizing.
+    pub fn new(target: F) -> Self {
+        TargetFitness { target }
+    }
+}
+
+impl<F: Fitness> Termination<F> for TargetFitness<F> {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if !progress.objective.is_better(&self.target, &progress.current().best) {
+            Some(TerminationReason::TargetReached)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once a number of new generations have been bred.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxGenerations {
+    generations: u32,
+}
+
+impl MaxGenerations {
+    /// Creates a condition met once `generations` have been bred.
+    pub fn new(generations: u32) -> Self {
+        MaxGenerations { generations }
+    }
+}
+
+impl<F> Termination<F> for MaxGenerations {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if progress.current().generation >= self.generations {
+            Some(TerminationReason::MaxGenerations)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once a number of fitness evaluations have been performed
+/// within it. Evaluations made by earlier runs are not counted.
+#[derive(Clone, Copy, Debug)]
+pub struct MaxEvaluations {
+    evaluations: usize,
+}
+
+impl MaxEvaluations {
+    /// Creates a condition met once `evaluations` fitness evaluations have
+    /// been performed.
+    pub fn new(evaluations: usize) -> Self {
+        MaxEvaluations { evaluations }
+    }
+}
+
+impl<F> Termination<F> for MaxEvaluations {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if progress.evaluations >= self.evaluations {
+            Some(TerminationReason::MaxEvaluations)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once it has taken longer than a time limit. The limit is only
+/// checked between generations, and so a run may overrun by the time it takes
+/// to breed and evaluate one generation. Time spent in earlier runs is not
+/// counted.
+#[derive(Clone, Copy, Debug)]
+pub struct TimeLimit {
+    limit: Duration,
+}
+
+impl TimeLimit {
+    /// Creates a condition met once the run has taken `limit`.
+    pub fn new(limit: Duration) -> Self {
+        TimeLimit { limit }
+    }
+}
+
+impl<F> Termination<F> for TimeLimit {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if progress.elapsed >= self.limit {
+            Some(TerminationReason::TimeLimit)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once the best fitness has not improved for a number of
+/// generations.
+#[derive(Clone, Copy, Debug)]
+pub struct Stagnation {
+    generations: usize,
+}
+
+impl Stagnation {
+    /// Creates a condition met when none of the last `generations` (> 0)
+    /// improved upon the best fitness seen before them.
+    pub fn new(generations: usize) -> Self {
+        assert!(generations > 0);
+        Stagnation { generations }
+    }
+}
+
+impl<F: Fitness> Termination<F> for Stagnation {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if stagnated(progress.history, self.generations, progress.objective) {
+            Some(TerminationReason::Stagnated)
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns true if none of the last `generations` of a history improved upon
+/// the best fitness seen before them.
+pub(crate) fn stagnated<F: Fitness>(
+    history: &[GenerationStats<F>],
+    generations: usize,
+    objective: Objective,
+) -> bool {
+    if history.len() <= generations {
+        return false;
+    }
+
+    let split = history.len() - generations;
+    let recent = objective.best(history[split..].iter().map(|s| &s.best));
+    let earlier = objective.best(history[..split].iter().map(|s| &s.best));
+    match (recent, earlier) {
+        (Some(recent), Some(earlier)) => !objective.is_better(recent, earlier),
+        _ => false,
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run once the population has converged, where the variance of
+/// fitness across a generation falls below an epsilon. Never met when the
+/// fitness is not a single number.
+#[derive(Clone, Copy, Debug)]
+pub struct Convergence {
+    epsilon: f64,
+}
+
+impl Convergence {
+    /// Creates a condition met when the fitness variance is below `epsilon`.
+    pub fn new(epsilon: f64) -> Self {
+        Convergence { epsilon }
+    }
+}
+
+impl<F> Termination<F> for Convergence {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        if progress.current().std_dev.is_some_and(|std_dev| std_dev.powi(2) < self.epsilon) {
+            Some(TerminationReason::Converged)
+        } else {
+            None
+        }
+    }
+}
+
+//------------------------------------------------------------------------------
+
+/// Ends the run when any of a group of conditions is met, reporting the reason
+/// of the first condition met.
+pub struct Any<F = f64> {
+    conditions: Vec<Box<dyn Termination<F>>>,
+}
+
+impl<F> Any<F> {
+    /// Creates a condition met when any of `conditions` are met.
+    pub fn new(conditions: Vec<Box<dyn Termination<F>>>) -> Self {
+        Any { conditions }
+    }
+}
+
+impl<F> Termination<F> for Any<F> {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        self.conditions.iter().find_map(|c| c.check(progress))
+    }
+}
+
+/// Ends the run only when all of a group of conditions are met, reporting the
+/// reasons of each condition.
+pub struct All<F = f64> {
+    conditions: Vec<Box<dyn Termination<F>>>,
+}
+
+impl<F> All<F> {
+    /// Creates a condition met when all of `conditions` are met.
+    pub fn new(conditions: Vec<Box<dyn Termination<F>>>) -> Self {
+        All { conditions }
+    }
+}
+
+impl<F> Termination<F> for All<F> {
+    fn check(&self, progress: &Progress<F>) -> Option<TerminationReason> {
+        let reasons = self.conditions
+            .iter()
+            .map(|c| c.check(progress))
+            .collect::<Option<Vec<TerminationReason>>>();
+
+        match reasons {
+            Some(ref r) if r.is_empty() => None,
+            Some(r) => Some(TerminationReason::All(r)),
+            None => None,
+        }
+    }
+}
@@ -20,15 +20,21 @@
 
 extern crate rand;
 
-use unit::Unit;
+use unit::{ScalarUnit, Unit};
+use multi_objective::MultiObjectiveUnit;
+use rand::Rng;
 use rand::distributions::{IndependentSample, Range};
 
+use std::cell::Cell;
+use std::error::Error;
+
 #[derive(Default, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct MockUnit {
     fitness: f64,
 }
 
-impl Unit for MockUnit {
+impl ScalarUnit for MockUnit {
     fn fitness(&self) -> f64 {
         self.fitness
     }
@@ -44,7 +50,7 @@ struct FloatyUnit {
     y: f64,
 }
 
-impl Unit for FloatyUnit {
+impl ScalarUnit for FloatyUnit {
     fn fitness(&self) -> f64 {
         (self.x + self.y) / 2.0
     }
@@ -57,30 +63,190 @@ impl Unit for FloatyUnit {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 struct TendUnit {
     towards: f64,
     x: f64,
 }
 
-impl Unit for TendUnit {
+impl ScalarUnit for TendUnit {
     fn fitness(&self) -> f64 {
         -(self.towards - self.x).abs()
     }
 
     fn breed_with(&self, other: &Self) -> Self {
+        ScalarUnit::breed_with_rng(self, other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
         let between = Range::new(-0.1, 0.1);
         TendUnit {
-            x: ((self.x + other.x) / 2.0) + between.ind_sample(&mut rand::thread_rng()),
+            x: ((self.x + other.x) / 2.0) + between.ind_sample(rng),
+            towards: self.towards,
+        }
+    }
+}
+
+/// A unit ranked first by how close `x` is to 5 and then by how close `y` is
+/// to 0.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+struct LexUnit {
+    x: i64,
+    y: f64,
+}
+
+impl Unit for LexUnit {
+    type Fitness = (i64, f64);
+
+    fn fitness(&self) -> (i64, f64) {
+        (-(5 - self.x).abs(), -self.y.abs())
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        LexUnit {
+            x: if rng.gen() { self.x } else { other.x } + Range::new(-1, 2).ind_sample(rng),
+            y: ((self.y + other.y) / 2.0) + Range::new(-0.1, 0.1).ind_sample(rng),
+        }
+    }
+}
+
+/// Schaffer's problem, where both `x^2` and `(x - 2)^2` are minimized and the
+/// Pareto front lies where `x` is within [0, 2].
+#[derive(Clone, Debug)]
+struct SchafferUnit {
+    x: f64,
+}
+
+impl MultiObjectiveUnit for SchafferUnit {
+    fn objectives(&self) -> Vec<f64> {
+        vec![self.x.powi(2), (self.x - 2.0).powi(2)]
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let between = Range::new(-0.5, 0.5);
+        SchafferUnit { x: ((self.x + other.x) / 2.0) + between.ind_sample(rng) }
+    }
+}
+
+#[derive(Default, Clone, PartialEq)]
+struct CostUnit {
+    towards: f64,
+    x: f64,
+}
+
+impl Unit for CostUnit {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        (self.towards - self.x).abs()
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        self.breed_with_rng(other, &mut rand::thread_rng())
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        let between = Range::new(-0.1, 0.1);
+        CostUnit {
+            x: ((self.x + other.x) / 2.0) + between.ind_sample(rng),
             towards: self.towards,
         }
     }
 }
 
+#[derive(Default, Clone, PartialEq)]
+struct DecayUnit {
+    fitness: f64,
+}
+
+impl Unit for DecayUnit {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.fitness
+    }
+
+    fn breed_with(&self, _: &Self) -> Self {
+        DecayUnit { fitness: self.fitness * 0.5 }
+    }
+}
+
+#[derive(Clone)]
+struct FlakyUnit {
+    fitness: f64,
+    failures: Cell<u32>,
+    panics: bool,
+}
+
+impl FlakyUnit {
+    fn new(fitness: f64, failures: u32, panics: bool) -> Self {
+        FlakyUnit {
+            fitness,
+            failures: Cell::new(failures),
+            panics,
+        }
+    }
+}
+
+impl Unit for FlakyUnit {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        self.try_fitness().expect("flaky unit failed")
+    }
+
+    fn try_fitness(&self) -> Result<f64, Box<dyn Error + Send + Sync>> {
+        if self.failures.get() == 0 {
+            return Ok(self.fitness);
+        }
+        self.failures.set(self.failures.get() - 1);
+        if self.panics {
+            panic!("flaky unit panicked");
+        }
+        Err("flaky unit failed".into())
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        FlakyUnit::new((self.fitness + other.fitness) / 2.0, 0, false)
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::redundant_field_names, clippy::get_first)]
 mod tests {
-    use test::{TendUnit, MockUnit, FloatyUnit};
-    use population::Population;
+    use test::{TendUnit, MockUnit, FloatyUnit, DecayUnit, FlakyUnit, CostUnit, LexUnit,
+               SchafferUnit};
+    use population::{LazyUnit, Population, PopulationError};
+    use report::TerminationReason;
+    use observer::{Control, GenerationView};
+    use hall_of_fame::HallOfFame;
+    use evaluator::{Failure, FailurePolicy, ThreadPool};
+    use fitness::{NonFinitePolicy, Objective};
+    use multi_objective::{self, Algorithm, Decomposition, MultiObjectivePopulation};
+    use pareto::{self, ParetoArchive};
+    use genome::bit_string::{BitCrossover, BitGenome};
+    use genome::mixed::{Gene, MixedCrossover, MixedGenome, Value};
+    use genome::permutation::{PermutationCrossover, PermutationGenome, PermutationMutation};
+    use genome::real_vector::{RealCrossover, RealGenome, RealMutation};
+    use unit::Unit;
+    use termination::{Convergence, MaxEvaluations, MaxGenerations, Stagnation, TargetFitness,
+                      Termination, TimeLimit};
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+    use selection::{Boltzmann, Rank, RouletteWheel, Selection, StochasticUniversalSampling,
+                    Tournament, Truncation};
+    use rand::{Rng, SeedableRng, StdRng};
 
     #[test]
     fn simple_compilation_test() {
@@ -220,4 +386,1585 @@ mod tests {
         assert_eq!(best_unit_one.x, best_unit_two.x);
         assert_eq!(best_unit_one.y, best_unit_two.y);
     }
+
+    #[test]
+    fn seeded_breeding_test() {
+        let towards = 10.0;
+        let test_vec = vec![
+            TendUnit {
+                x: 0.1,
+                towards,
+            },
+            TendUnit {
+                x: 2.3,
+                towards,
+            },
+        ];
+
+        let serial_units = Population::new(test_vec.clone())
+            .set_size(100)
+            .set_rand_seed(7)
+            .set_breed_factor(0.25)
+            .epochs(50)
+            .finish();
+
+        for &n_processes in &[1, 2, 3, 4, 16, 64] {
+            let parallel_units = Population::new(test_vec.clone())
+                .set_size(100)
+                .set_rand_seed(7)
+                .set_breed_factor(0.25)
+                .epochs_parallel(50, n_processes)
+                .finish();
+
+            assert_eq!(serial_units.len(), parallel_units.len());
+            for (a, b) in serial_units.iter().zip(parallel_units.iter()) {
+                assert_eq!(a.x, b.x);
+            }
+        }
+
+        let other_seed_unit = Population::new(test_vec.clone())
+            .set_size(100)
+            .set_rand_seed(8)
+            .set_breed_factor(0.25)
+            .epochs(50)
+            .finish()
+            .first()
+            .unwrap()
+            .clone();
+
+        assert!(other_seed_unit.x != serial_units[0].x);
+    }
+
+    #[test]
+    fn selection_strategies_test() {
+        fn converges<S: Selection + 'static>(selection: S) {
+            let towards = 10.0;
+            let test_vec = vec![
+                TendUnit { x: 0.1, towards },
+                TendUnit { x: 2.3, towards },
+            ];
+
+            let best_unit = Population::new(test_vec)
+                .set_size(100)
+                .set_breed_factor(0.5)
+                .set_survival_factor(0.1)
+                .set_selection(selection)
+                .epochs(300)
+                .finish()
+                .first()
+                .unwrap()
+                .clone();
+
+            assert_eq!(best_unit.x.round(), towards);
+        }
+
+        converges(Truncation);
+        converges(Tournament::new(3));
+        converges(RouletteWheel);
+        converges(StochasticUniversalSampling);
+        converges(Rank::linear(1.8));
+        converges(Rank::exponential(0.9));
+        converges(Boltzmann::new(0.5));
+    }
+
+    #[test]
+    fn selection_bias_test() {
+        let fitnesses = vec![4.0, 3.0, 2.0, 1.0, 0.0];
+        let seed: &[_] = &[1];
+        let mut rng: StdRng = SeedableRng::from_seed(seed);
+
+        let strategies: Vec<Box<dyn Selection>> = vec![
+            Box::new(Tournament::new(2)),
+            Box::new(RouletteWheel),
+            Box::new(StochasticUniversalSampling),
+            Box::new(Rank::linear(2.0)),
+            Box::new(Rank::exponential(0.5)),
+            Box::new(Boltzmann::new(1.0)),
+        ];
+
+        for strategy in strategies {
+            let mut counts = [0; 5];
+            for (a, b) in strategy.select(&fitnesses, 1000, &mut rng) {
+                counts[a] += 1;
+                counts[b] += 1;
+            }
+            assert!(counts[0] > counts[2]);
+            assert!(counts[2] > counts[4]);
+        }
+    }
+
+    #[test]
+    fn report_test() {
+        let mut population =
+            Population::new(vec![MockUnit { fitness: 0.2 }, MockUnit { fitness: 0.1 }]);
+        population
+            .set_size(10)
+            .set_breed_factor(1.0)
+            .set_termination(TargetFitness::new(1.0))
+            .epochs(100);
+
+        {
+            let report = population.report().unwrap();
+            assert_eq!(report.termination, Some(TerminationReason::TargetReached));
+            assert_eq!(report.generations, 1);
+            assert_eq!(report.evaluations, 11);
+            assert_eq!(report.history.len(), 2);
+            assert_eq!(report.history[0].best, 0.2);
+            assert_eq!(report.history[0].worst, 0.1);
+            assert!((report.history[0].mean.unwrap() - 0.15).abs() < 1e-9);
+            assert!((report.history[0].std_dev.unwrap() - 0.05).abs() < 1e-9);
+            assert_eq!(report.history[1].evaluations, 9);
+            assert_eq!(report.last().unwrap().best, 1.0);
+        }
+
+        let towards = 10.0;
+        let mut population = Population::new(vec![
+            TendUnit { x: 0.1, towards },
+            TendUnit { x: 2.3, towards },
+        ]);
+        population.set_size(50).epochs_parallel(20, 2);
+
+        let report = population.report().unwrap();
+        assert_eq!(report.termination, Some(TerminationReason::Completed));
+        assert_eq!(report.generations, 20);
+        assert_eq!(report.history.len(), 21);
+        for (i, stats) in report.history.iter().enumerate() {
+            assert_eq!(stats.generation, i as u32);
+            assert!(Some(stats.best) >= stats.mean && stats.mean >= Some(stats.worst));
+        }
+        assert!(report.history[20].best > report.history[0].best);
+    }
+
+    #[test]
+    fn observer_test() {
+        let towards = 10.0;
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let seen_clone = seen.clone();
+
+        let mut population = Population::new(vec![
+            TendUnit { x: 0.1, towards },
+            TendUnit { x: 2.3, towards },
+        ]);
+        population
+            .set_size(50)
+            .add_observer(move |view: &GenerationView<TendUnit>| {
+                let (best, &fitness) = view.best().unwrap();
+                assert_eq!(fitness, -(best.towards - best.x).abs());
+                assert_eq!(fitness, view.stats().best);
+                assert_eq!(view.iter().count(), view.len());
+
+                seen_clone.lock().unwrap().push(view.generation());
+                if view.generation() == 5 {
+                    Control::Stop
+                } else {
+                    Control::Continue
+                }
+            })
+            .epochs_parallel(100, 2);
+
+        assert_eq!(*seen.lock().unwrap(), vec![0, 1, 2, 3, 4, 5]);
+
+        let report = population.report().unwrap();
+        assert_eq!(report.termination, Some(TerminationReason::Stopped));
+        assert_eq!(report.generations, 5);
+    }
+
+    #[test]
+    fn termination_test() {
+        fn run<C: Termination + 'static>(termination: C) -> (TerminationReason, u32, usize) {
+            let mut population =
+                Population::new(vec![MockUnit { fitness: 0.2 }, MockUnit { fitness: 0.1 }]);
+            population
+                .set_size(10)
+                .set_breed_factor(1.0)
+                .set_termination(termination)
+                .epochs(100);
+
+            let report = population.report().unwrap();
+            (report.termination.clone().unwrap(), report.generations, report.evaluations)
+        }
+
+        assert_eq!(run(TargetFitness::new(1.0)), (TerminationReason::TargetReached, 1, 11));
+        assert_eq!(run(MaxGenerations::new(10)), (TerminationReason::MaxGenerations, 10, 56));
+        assert_eq!(run(MaxEvaluations::new(20)), (TerminationReason::MaxEvaluations, 3, 21));
+        assert_eq!(run(TimeLimit::new(Duration::from_secs(0))), (TerminationReason::TimeLimit, 0, 2));
+        assert_eq!(run(Stagnation::new(3)), (TerminationReason::Stagnated, 4, 26));
+        assert_eq!(run(Convergence::new(1e-12)), (TerminationReason::Converged, 2, 16));
+        assert_eq!(run(TargetFitness::new(2.0)), (TerminationReason::Completed, 100, 506));
+
+        assert_eq!(
+            run(TargetFitness::new(2.0).or(MaxGenerations::new(3))),
+            (TerminationReason::MaxGenerations, 3, 21)
+        );
+        assert_eq!(
+            run(MaxGenerations::new(5).and(MaxEvaluations::new(1))),
+            (
+                TerminationReason::All(vec![
+                    TerminationReason::MaxGenerations,
+                    TerminationReason::MaxEvaluations,
+                ]),
+                5,
+                31
+            )
+        );
+
+        // Limits apply to each run rather than to the life of the population.
+        let mut population =
+            Population::new(vec![MockUnit { fitness: 0.2 }, MockUnit { fitness: 0.1 }]);
+        population
+            .set_size(10)
+            .set_breed_factor(1.0)
+            .set_termination(MaxEvaluations::new(20))
+            .epochs(100);
+        assert_eq!(population.report().unwrap().generations, 3);
+        population.epochs(100);
+        assert_eq!(population.report().unwrap().generations, 7);
+        assert_eq!(population.report().unwrap().evaluations, 41);
+        assert_eq!(population.generations().count(), 4);
+    }
+
+    #[test]
+    fn step_test() {
+        let towards = 10.0;
+        let test_vec = vec![
+            TendUnit { x: 0.1, towards },
+            TendUnit { x: 2.3, towards },
+        ];
+
+        let mut stepped = Population::new(test_vec.clone());
+        stepped.set_size(50).set_rand_seed(3);
+        for i in 1..6 {
+            let view = stepped.step();
+            assert_eq!(view.generation(), i);
+            assert_eq!(view.len(), 50);
+        }
+        stepped.epochs(5);
+
+        {
+            let report = stepped.report().unwrap();
+            assert_eq!(report.generations, 10);
+            assert_eq!(report.history.len(), 11);
+        }
+
+        let stepped_units = stepped.finish();
+        let run_units = Population::new(test_vec.clone())
+            .set_size(50)
+            .set_rand_seed(3)
+            .epochs(10)
+            .finish();
+
+        for (a, b) in stepped_units.iter().zip(run_units.iter()) {
+            assert_eq!(a.x, b.x);
+        }
+
+        let mut population = Population::new(test_vec);
+        population.set_size(50);
+        let snapshots: Vec<_> = population.generations().take(5).collect();
+        assert_eq!(snapshots.len(), 5);
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            assert_eq!(snapshot.stats.generation, i as u32 + 1);
+            assert_eq!(snapshot.fitness, snapshot.stats.best);
+            assert_eq!(snapshot.fitness, -(towards - snapshot.best.x).abs());
+        }
+        assert_eq!(population.report().unwrap().generations, 5);
+
+        let mut population =
+            Population::new(vec![MockUnit { fitness: 0.2 }, MockUnit { fitness: 0.1 }]);
+        population.set_size(10).set_termination(TargetFitness::new(1.0));
+        assert_eq!(population.generations().count(), 1);
+        assert_eq!(
+            population.report().unwrap().termination,
+            Some(TerminationReason::TargetReached)
+        );
+    }
+
+    #[test]
+    fn ranked_test() {
+        let mut population =
+            Population::new(vec![MockUnit { fitness: 0.2 }, MockUnit { fitness: 0.1 }]);
+        assert!(population.best().is_none());
+        assert_eq!(population.best_fitness(), None);
+
+        let ranked = population.finish_ranked();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].fitness, 0.2);
+        assert_eq!(ranked[1].fitness, 0.1);
+
+        let towards = 10.0;
+        let mut population = Population::new(vec![
+            TendUnit { x: 0.1, towards },
+            TendUnit { x: 2.3, towards },
+        ]);
+        population.set_size(50).epochs(20);
+
+        let best_x = population.best().unwrap().x;
+        let best_fitness = population.best_fitness().unwrap();
+        assert_eq!(best_fitness, -(towards - best_x).abs());
+
+        let ranked = population.finish_ranked();
+        assert_eq!(ranked.len(), 50);
+        assert_eq!(ranked[0].unit.x, best_x);
+        assert_eq!(ranked[0].fitness, best_fitness);
+        for pair in ranked.windows(2) {
+            assert!(pair[0].fitness >= pair[1].fitness);
+            assert_eq!(pair[1].fitness, -(towards - pair[1].unit.x).abs());
+        }
+        assert!(population.best().is_none());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn checkpoint_test() {
+        use std::env;
+        use std::fs;
+        use std::process;
+
+        let path = env::temp_dir().join(format!("spiril_checkpoint_test_{}", process::id()));
+
+        let towards = 10.0;
+        let test_vec = vec![
+            TendUnit { x: 0.1, towards },
+            TendUnit { x: 2.3, towards },
+        ];
+
+        Population::new(test_vec.clone())
+            .set_size(50)
+            .set_rand_seed(4)
+            .set_breed_factor(0.3)
+            .set_checkpoint(&path, 5)
+            .epochs_parallel(10, 2);
+
+        match Population::new(vec![TendUnit { x: 0.1, towards }]).try_set_checkpoint(&path, 0) {
+            Err(PopulationError::InvalidCheckpointInterval(0)) => (),
+            _ => panic!("expected an invalid checkpoint interval"),
+        }
+
+        let mut restored = Population::<TendUnit>::load_checkpoint(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(restored.checkpoint_error().is_none());
+        assert_eq!(restored.report().unwrap().generations, 10);
+        assert_eq!(restored.report().unwrap().history.len(), 11);
+        assert!(restored.best_fitness().is_some());
+
+        let resumed_units = restored.epochs(10).finish();
+        assert_eq!(restored.report().unwrap().generations, 20);
+
+        let uninterrupted_units = Population::new(test_vec)
+            .set_size(50)
+            .set_rand_seed(4)
+            .set_breed_factor(0.3)
+            .epochs(20)
+            .finish();
+
+        assert_eq!(resumed_units.len(), uninterrupted_units.len());
+        for (a, b) in resumed_units.iter().zip(uninterrupted_units.iter()) {
+            assert_eq!(a.x, b.x);
+        }
+
+        // Non-finite fitness survives a round trip.
+        let mut population = Population::new(vec![
+            MockUnit { fitness: 0.5 },
+            MockUnit { fitness: f64::NAN },
+            MockUnit { fitness: f64::INFINITY },
+        ]);
+        population.epochs(0).save_checkpoint(&path).unwrap();
+        let mut restored = Population::<MockUnit>::load_checkpoint(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(
+            format!("{:?}", restored.report().unwrap().history),
+            format!("{:?}", population.report().unwrap().history)
+        );
+        let fitnesses: Vec<f64> = restored.finish_ranked().iter().map(|r| r.fitness).collect();
+        assert_eq!(fitnesses[..2], [f64::INFINITY, 0.5]);
+        assert!(fitnesses[2].is_nan());
+
+        // As does fitness that is not a single number.
+        let mut population =
+            Population::new(vec![LexUnit { x: 0, y: 2.0 }, LexUnit { x: 1, y: -2.0 }]);
+        population.set_size(10).set_rand_seed(2).epochs(5).save_checkpoint(&path).unwrap();
+        let mut restored = Population::<LexUnit>::load_checkpoint(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(restored.report().unwrap().history, population.report().unwrap().history);
+        assert_eq!(restored.report().unwrap().history[0].mean, None);
+        assert_eq!(restored.best_fitness(), population.best_fitness());
+        let resumed: Vec<(i64, f64)> =
+            restored.epochs(5).finish().iter().map(|u| (u.x, u.y)).collect();
+        let uninterrupted: Vec<(i64, f64)> =
+            population.epochs(5).finish().iter().map(|u| (u.x, u.y)).collect();
+        assert_eq!(resumed, uninterrupted);
+    }
+
+    #[test]
+    fn elitism_test() {
+        let towards = 10.0;
+        let mut population = Population::new(vec![
+            TendUnit { x: 0.3, towards },
+            TendUnit { x: 0.7, towards },
+        ]);
+        population
+            .set_size(20)
+            .set_breed_factor(0.5)
+            .set_survival_factor(0.0)
+            .set_elitism(2)
+            .epochs(200);
+
+        let report = population.report().unwrap();
+        for pair in report.history.windows(2) {
+            assert!(pair[1].best >= pair[0].best);
+        }
+
+        // Elites are carried with their cached fitness, and so are not
+        // evaluated again.
+        for stats in &report.history[1..] {
+            assert_eq!(stats.evaluations, 18);
+        }
+    }
+
+    #[test]
+    fn hall_of_fame_test() {
+        let towards = 10.0;
+        let mut population = Population::new(vec![
+            TendUnit { x: 0.3, towards },
+            TendUnit { x: 0.7, towards },
+        ]);
+        population
+            .set_size(20)
+            .set_survival_factor(0.0)
+            .set_hall_of_fame(HallOfFame::new(5))
+            .epochs(100);
+
+        let best_ever = population
+            .report()
+            .unwrap()
+            .history
+            .iter()
+            .map(|s| s.best)
+            .fold(f64::MIN, f64::max);
+
+        let hall_of_fame = population.hall_of_fame().unwrap();
+        assert_eq!(hall_of_fame.len(), 5);
+        assert_eq!(hall_of_fame.best().unwrap().fitness, best_ever);
+        for (i, entry) in hall_of_fame.entries().iter().enumerate() {
+            assert_eq!(entry.fitness, -(towards - entry.unit.x).abs());
+            assert!(entry.generation <= 100);
+            for other in &hall_of_fame.entries()[i + 1..] {
+                assert!(entry.fitness >= other.fitness);
+                assert!(entry.unit != other.unit);
+            }
+        }
+
+        // Children are always weaker than their parents, and so without the
+        // hall of fame the best unit is lost.
+        let mut population =
+            Population::new(vec![DecayUnit { fitness: 0.8 }, DecayUnit { fitness: 0.4 }]);
+        population
+            .set_size(10)
+            .set_breed_factor(1.0)
+            .set_survival_factor(0.0)
+            .epochs(2);
+        assert_eq!(population.best_fitness(), Some(0.2));
+
+        let mut population =
+            Population::new(vec![DecayUnit { fitness: 0.8 }, DecayUnit { fitness: 0.4 }]);
+        population
+            .set_size(10)
+            .set_breed_factor(1.0)
+            .set_survival_factor(0.0)
+            .set_hall_of_fame(HallOfFame::new(2).reinject_after(2))
+            .epochs(2);
+        assert_eq!(population.best_fitness(), Some(0.8));
+        assert_eq!(population.hall_of_fame().unwrap().best().unwrap().generation, 0);
+
+        // Clones at the top of a converged generation do not crowd out the
+        // distinct units below them.
+        let units: Vec<LazyUnit<DecayUnit>> = [10.0, 10.0, 10.0, 10.0, 10.0, 9.0, 8.0, 7.0]
+            .iter()
+            .map(|&fitness| {
+                LazyUnit {
+                    unit: DecayUnit { fitness },
+                    lazy_fitness: Some(fitness),
+                }
+            })
+            .collect();
+        let mut hall_of_fame = HallOfFame::new(3);
+        hall_of_fame.record(&units, 0, Objective::Maximize);
+        let fitness: Vec<f64> = hall_of_fame.entries().iter().map(|e| e.fitness).collect();
+        assert_eq!(fitness, vec![10.0, 9.0, 8.0]);
+    }
+
+    #[test]
+    fn thread_pool_test() {
+        let towards = 10.0;
+        let test_vec = vec![
+            TendUnit {
+                x: 0.1,
+                towards,
+            },
+            TendUnit {
+                x: 2.3,
+                towards,
+            },
+        ];
+
+        let serial_units = Population::new(test_vec.clone())
+            .set_size(100)
+            .set_rand_seed(7)
+            .epochs(50)
+            .finish();
+
+        let thread_pool = ThreadPool::new(3);
+        assert_eq!(thread_pool.n_threads(), 3);
+
+        // Several populations share the pool at once.
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let thread_pool = thread_pool.clone();
+                let test_vec = test_vec.clone();
+                thread::spawn(move || {
+                    Population::new(test_vec)
+                        .set_size(100)
+                        .set_rand_seed(7)
+                        .set_thread_pool(&thread_pool)
+                        .epochs(50)
+                        .finish()
+                })
+            })
+            .collect();
+
+        for handle in handles {
+            let pooled_units = handle.join().unwrap();
+            assert_eq!(serial_units.len(), pooled_units.len());
+            for (a, b) in serial_units.iter().zip(pooled_units.iter()) {
+                assert_eq!(a.x, b.x);
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_breeding_test() {
+        let towards = 10.0;
+        let test_vec = vec![
+            TendUnit {
+                x: 0.1,
+                towards,
+            },
+            TendUnit {
+                x: 2.3,
+                towards,
+            },
+        ];
+
+        let serial_units = Population::new(test_vec.clone())
+            .set_size(100)
+            .set_rand_seed(7)
+            .set_elitism(2)
+            .epochs(50)
+            .finish();
+
+        for &n_processes in &[1, 3, 16] {
+            let parallel_units = Population::new(test_vec.clone())
+                .set_size(100)
+                .set_rand_seed(7)
+                .set_elitism(2)
+                .set_parallel_breeding(true)
+                .epochs_parallel(50, n_processes)
+                .finish();
+
+            assert_eq!(serial_units.len(), parallel_units.len());
+            for (a, b) in serial_units.iter().zip(parallel_units.iter()) {
+                assert_eq!(a.x, b.x);
+            }
+        }
+    }
+
+    #[test]
+    fn failure_policy_test() {
+        let flaky_units = |panics| {
+            vec![
+                FlakyUnit::new(0.5, 1, panics),
+                FlakyUnit::new(0.3, 0, false),
+                FlakyUnit::new(0.8, 0, false),
+            ]
+        };
+
+        // Aborting keeps the fitnesses already calculated, so a later run
+        // retries only the unit that failed.
+        let mut population = Population::new(flaky_units(false));
+        population.set_size(3);
+        {
+            let err = match population.try_epochs(5) {
+                Err(PopulationError::Evaluation(err)) => err,
+                _ => panic!("expected an evaluation error"),
+            };
+            assert_eq!(err.generation, 0);
+            assert_eq!(err.failed, 1);
+            match err.failure {
+                Failure::Error(ref err) => assert_eq!(err.to_string(), "flaky unit failed"),
+                _ => panic!("expected an error"),
+            }
+        }
+        assert!(population.report().is_none());
+        assert!(population.try_epochs(0).is_ok());
+        assert_eq!(population.report().unwrap().evaluations, 4);
+
+        // Panics on worker threads end the run rather than hanging it.
+        let err = match Population::new(flaky_units(true))
+            .set_size(3)
+            .try_epochs_parallel(5, 4) {
+            Err(PopulationError::Evaluation(err)) => err,
+            _ => panic!("expected an evaluation error"),
+        };
+        match err.failure {
+            Failure::Panic(ref message) => assert_eq!(message, "flaky unit panicked"),
+            _ => panic!("expected a panic"),
+        }
+
+        let fitnesses: Vec<f64> = Population::new(flaky_units(true))
+            .set_size(3)
+            .set_failure_policy(FailurePolicy::WorstFitness)
+            .epochs_parallel(0, 2)
+            .finish_ranked()
+            .iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![0.8, 0.3, 0.3]);
+
+        let fitnesses: Vec<f64> = Population::new(flaky_units(false))
+            .set_size(3)
+            .set_failure_policy(FailurePolicy::Discard)
+            .epochs(0)
+            .finish_ranked()
+            .iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![0.8, 0.3]);
+
+        let fitnesses: Vec<f64> = Population::new(flaky_units(true))
+            .set_size(3)
+            .set_failure_policy(FailurePolicy::Retry(1))
+            .epochs(0)
+            .finish_ranked()
+            .iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![0.8, 0.5, 0.3]);
+
+        assert!(Population::new(vec![FlakyUnit::new(0.5, 2, false)])
+            .set_failure_policy(FailurePolicy::Retry(1))
+            .try_epochs(1)
+            .is_err());
+
+        // When every unit fails there is nothing to carry on with.
+        assert!(Population::new(vec![FlakyUnit::new(0.5, 1, false)])
+            .set_failure_policy(FailurePolicy::WorstFitness)
+            .try_epochs(1)
+            .is_err());
+    }
+
+    #[test]
+    fn population_error_test() {
+        let mut population = Population::new(vec![MockUnit { fitness: 0.2 }]);
+
+        for &factor in &[0.0, -0.5, 1.5, f64::NAN] {
+            match population.try_set_breed_factor(factor) {
+                Err(PopulationError::InvalidBreedFactor(f)) => {
+                    assert!(f == factor || f.is_nan())
+                }
+                _ => panic!("expected an invalid breed factor"),
+            }
+        }
+        for &factor in &[-0.1, 1.1, f64::NAN] {
+            match population.try_set_survival_factor(factor) {
+                Err(PopulationError::InvalidSurvivalFactor(_)) => (),
+                _ => panic!("expected an invalid survival factor"),
+            }
+        }
+        match population.try_set_size(0) {
+            Err(PopulationError::InvalidSize(0)) => (),
+            _ => panic!("expected an invalid size"),
+        }
+        match population.try_epochs_parallel(3, 0) {
+            Err(PopulationError::InvalidThreads(0)) => (),
+            _ => panic!("expected an invalid number of processes"),
+        }
+
+        // Rejected values leave the population untouched.
+        assert!(population
+            .try_set_breed_factor(1.0)
+            .and_then(|p| p.try_set_survival_factor(0.0))
+            .and_then(|p| p.try_set_size(5))
+            .and_then(|p| p.try_epochs(3))
+            .is_ok());
+        assert_eq!(population.finish().len(), 5);
+
+        // The population is empty once finished.
+        match population.try_epochs(3) {
+            Err(PopulationError::EmptyPopulation) => (),
+            _ => panic!("expected an empty population"),
+        }
+        assert!(population.try_step().is_err());
+        assert!(Population::<MockUnit>::new(Vec::new())
+            .try_epochs_parallel(3, 2)
+            .is_err());
+    }
+
+    #[test]
+    fn non_finite_test() {
+        let non_finite_units = || {
+            vec![
+                MockUnit { fitness: f64::NAN },
+                MockUnit { fitness: 0.5 },
+                MockUnit { fitness: f64::NEG_INFINITY },
+                MockUnit { fitness: f64::INFINITY },
+                MockUnit { fitness: 0.2 },
+            ]
+        };
+
+        let fitnesses: Vec<f64> = Population::new(non_finite_units())
+            .set_size(5)
+            .epochs(0)
+            .finish_ranked()
+            .iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(fitnesses[..4], [f64::INFINITY, 0.5, 0.2, f64::NEG_INFINITY]);
+        assert!(fitnesses[4].is_nan());
+
+        let fitnesses: Vec<f64> = Population::new(non_finite_units())
+            .set_size(5)
+            .set_non_finite_policy(NonFinitePolicy::Reject)
+            .set_failure_policy(FailurePolicy::Discard)
+            .epochs(0)
+            .finish_ranked()
+            .iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(fitnesses, vec![0.5, 0.2]);
+
+        match Population::new(non_finite_units())
+            .set_non_finite_policy(NonFinitePolicy::Reject)
+            .try_epochs(1) {
+            Err(PopulationError::Evaluation(err)) => {
+                assert_eq!(err.failed, 3);
+                match err.failure {
+                    Failure::NonFinite(fitness) => assert!(fitness.is_nan()),
+                    _ => panic!("expected a non-finite fitness"),
+                }
+            }
+            _ => panic!("expected an evaluation error"),
+        }
+
+        // Proportional strategies never pick units with a fitness of NaN, and
+        // always pick units with an infinite fitness.
+        let mut rng: StdRng = SeedableRng::from_seed(&[1_usize][..]);
+        let strategies: Vec<Box<dyn Selection>> =
+            vec![Box::new(RouletteWheel), Box::new(Boltzmann::new(1.0))];
+        for strategy in &strategies {
+            for (a, b) in strategy.select(&[0.5, f64::NAN, 0.5], 50, &mut rng) {
+                assert!(a != 1 && b != 1);
+            }
+            for (a, b) in strategy.select(&[f64::INFINITY, 1.0, f64::NAN], 50, &mut rng) {
+                assert!(a == 0 && b == 0);
+            }
+        }
+
+        // Runs carry on regardless of non-finite fitnesses.
+        Population::new(non_finite_units())
+            .set_size(20)
+            .set_breed_factor(1.0)
+            .set_selection(RouletteWheel)
+            .set_termination(MaxGenerations::new(5))
+            .epochs(10);
+    }
+
+    #[test]
+    #[should_panic(expected = "which the non-finite policy does not allow")]
+    fn non_finite_panic_test() {
+        Population::new(vec![MockUnit { fitness: 0.5 }, MockUnit { fitness: f64::NAN }])
+            .set_non_finite_policy(NonFinitePolicy::Panic)
+            .epochs(1);
+    }
+
+    #[test]
+    fn objective_test() {
+        let mut population = Population::new(vec![
+            MockUnit { fitness: 0.2 },
+            MockUnit { fitness: 0.1 },
+            MockUnit { fitness: 0.5 },
+        ]);
+        population
+            .set_size(3)
+            .set_objective(Objective::Minimize)
+            .set_termination(TargetFitness::new(0.1))
+            .epochs(10);
+        {
+            let report = population.report().unwrap();
+            assert_eq!(report.termination, Some(TerminationReason::TargetReached));
+            assert_eq!(report.history[0].best, 0.1);
+            assert_eq!(report.history[0].worst, 0.5);
+        }
+        let fitnesses: Vec<f64> = population.finish_ranked().iter().map(|r| r.fitness).collect();
+        assert_eq!(fitnesses, vec![0.1, 0.2, 0.5]);
+
+        let towards = 10.0;
+        let mut population = Population::new(vec![
+            CostUnit { x: 0.1, towards },
+            CostUnit { x: 2.3, towards },
+        ]);
+        population
+            .set_size(100)
+            .set_breed_factor(0.25)
+            .set_objective(Objective::Minimize)
+            .set_selection(RouletteWheel)
+            .set_hall_of_fame(HallOfFame::new(3))
+            .set_termination(Stagnation::new(20).or(TargetFitness::new(0.01)))
+            .epochs(1000);
+
+        let best = population.hall_of_fame().unwrap().best().unwrap().fitness;
+        assert_eq!(Some(best), population.best_fitness());
+        assert!(best <= 0.01);
+        assert_eq!(population.best().unwrap().x.round(), towards);
+
+        let report = population.report().unwrap();
+        for stats in &report.history {
+            assert!(Some(stats.best) <= stats.mean && stats.mean <= Some(stats.worst));
+        }
+        assert_eq!(report.termination, Some(TerminationReason::TargetReached));
+    }
+
+    #[test]
+    fn generic_fitness_test() {
+        let mut population = Population::new(vec![
+            LexUnit { x: 5, y: 3.0 },
+            LexUnit { x: 4, y: 0.0 },
+            LexUnit { x: 5, y: -1.0 },
+        ]);
+        let ranked: Vec<(i64, f64)> = population
+            .set_size(3)
+            .finish_ranked()
+            .into_iter()
+            .map(|r| r.fitness)
+            .collect();
+        assert_eq!(ranked, vec![(0, -1.0), (0, -3.0), (-1, 0.0)]);
+
+        let mut population = Population::new(vec![
+            LexUnit { x: 0, y: 2.0 },
+            LexUnit { x: 1, y: -2.0 },
+        ]);
+        population
+            .set_size(50)
+            .set_selection(RouletteWheel)
+            .set_termination(TargetFitness::new((0, -0.05)).or(MaxGenerations::new(1000)))
+            .epochs(1000);
+
+        let report = population.report().unwrap();
+        assert_eq!(report.termination, Some(TerminationReason::TargetReached));
+        assert_eq!(report.history[0].mean, None);
+        assert_eq!(report.history[0].std_dev, None);
+        assert_eq!(report.history[0].worst.0, -5);
+        assert_eq!(population.best().unwrap().x, 5);
+        assert!(population.best_fitness().unwrap().1 >= -0.05);
+    }
+
+    #[test]
+    fn nsga2_test() {
+        let points = vec![
+            vec![1.0, 4.0],
+            vec![2.0, 2.0],
+            vec![4.0, 1.0],
+            vec![1.0, 1.0],
+            vec![0.0, 0.0],
+            vec![2.0, 2.0],
+        ];
+        assert_eq!(
+            pareto::non_dominated_sort(&points, &[Objective::Maximize; 2]),
+            vec![vec![0, 1, 2, 5], vec![3], vec![4]]
+        );
+        let distances = multi_objective::crowding_distances(&points, &[0, 1, 2]);
+        assert_eq!(distances, vec![f64::INFINITY, 2.0, f64::INFINITY]);
+
+        let run = |thread_pool: Option<&ThreadPool>| {
+            let mut population = MultiObjectivePopulation::new(vec![
+                SchafferUnit { x: -8.0 },
+                SchafferUnit { x: 9.0 },
+            ]);
+            population
+                .set_size(40)
+                .set_rand_seed(7)
+                .set_objectives(vec![Objective::Minimize, Objective::Minimize]);
+            if let Some(thread_pool) = thread_pool {
+                population.set_thread_pool(thread_pool);
+            }
+            population.epochs(50);
+            assert_eq!(population.generations(), 50);
+            population.finish()
+        };
+
+        let solutions = run(None);
+        assert_eq!(solutions.len(), 40);
+        let front: Vec<_> = solutions.iter().filter(|s| s.rank == 0).collect();
+        assert!(front.len() > 10);
+        for solution in &front {
+            assert!(solution.unit.x > -0.1 && solution.unit.x < 2.1);
+            assert!(!front.iter().any(|other| {
+                other.objectives[0] < solution.objectives[0]
+                    && other.objectives[1] < solution.objectives[1]
+            }));
+        }
+
+        let parallel = run(Some(&ThreadPool::new(4)));
+        let xs = |solutions: &[multi_objective::Solution<SchafferUnit>]| {
+            solutions.iter().map(|s| s.unit.x).collect::<Vec<f64>>()
+        };
+        assert_eq!(xs(&solutions), xs(&parallel));
+
+        let mut population = MultiObjectivePopulation::new(vec![SchafferUnit { x: 1.0 }]);
+        match population.try_epochs_parallel(3, 0) {
+            Err(PopulationError::InvalidThreads(0)) => (),
+            _ => panic!("expected an invalid number of processes"),
+        }
+
+        population.set_objectives(vec![Objective::Minimize; 3]);
+        match population.try_epochs(3) {
+            Err(PopulationError::ObjectiveCountMismatch { expected: 3, found: 2 }) => (),
+            _ => panic!("expected an objective count mismatch"),
+        }
+        population.set_objectives(vec![Objective::Minimize; 2]);
+        assert!(population.try_epochs(3).is_ok());
+    }
+
+    #[test]
+    fn pareto_test() {
+        use self::Objective::{Maximize, Minimize};
+
+        assert!(pareto::dominates(&[1.0, 5.0], &[2.0, 4.0], &[Minimize, Maximize]));
+        assert!(!pareto::dominates(&[1.0, 5.0], &[2.0, 4.0], &[Maximize, Maximize]));
+        assert!(!pareto::dominates(&[1.0, 1.0], &[1.0, 1.0], &[Minimize, Minimize]));
+        assert!(pareto::epsilon_dominates(&[1.1, 1.0], &[1.0, 1.0], &[0.2, 0.2], &[Minimize; 2]));
+
+        let front = vec![vec![1.0, 3.0], vec![2.0, 2.0], vec![3.0, 1.0], vec![3.0, 3.0]];
+        assert_eq!(pareto::hypervolume(&front, &[4.0, 4.0], &[Minimize; 2]), 6.0);
+        let maximized = vec![vec![3.0, 1.0], vec![2.0, 2.0], vec![1.0, 3.0]];
+        assert_eq!(pareto::hypervolume(&maximized, &[0.0, 0.0], &[Maximize; 2]), 6.0);
+        let cubes = vec![vec![0.0, 0.0, 1.0], vec![1.0, 1.0, 0.0]];
+        assert_eq!(pareto::hypervolume(&cubes, &[2.0, 2.0, 2.0], &[Minimize; 3]), 5.0);
+
+        // Lifting points into a fourth dimension scales their hypervolume,
+        // which checks WFG against the three dimensional sweep.
+        let mut rng: StdRng = SeedableRng::from_seed(&[3usize][..]);
+        let points: Vec<Vec<f64>> = (0..20)
+            .map(|_| vec![rng.gen(), rng.gen(), rng.gen()])
+            .collect();
+        let lifted: Vec<Vec<f64>> = points
+            .iter()
+            .map(|p| vec![p[0], p[1], 0.5, p[2]])
+            .collect();
+        let volume = pareto::hypervolume(&points, &[1.0; 3], &[Minimize; 3]);
+        let lifted_volume = pareto::hypervolume(&lifted, &[1.0, 1.0, 2.0, 1.0], &[Minimize; 4]);
+        assert!(volume > 0.0);
+        assert!((volume * 1.5 - lifted_volume).abs() < 1e-9);
+
+        let reference: Vec<Vec<f64>> = (0..5).map(|i| vec![i as f64, 4.0 - i as f64]).collect();
+        assert_eq!(pareto::igd(&reference, &reference), 0.0);
+        assert_eq!(pareto::spread(&reference, &reference, &[Minimize; 2]), 0.0);
+        let clustered = vec![vec![0.0, 4.0], vec![0.5, 3.5], vec![1.0, 3.0]];
+        assert!(pareto::igd(&clustered, &reference) > 0.0);
+        assert!(pareto::spread(&clustered, &reference, &[Minimize; 2]) > 0.5);
+
+        let mut archive = ParetoArchive::new(vec![Minimize, Minimize]);
+        assert!(archive.insert("a", vec![2.0, 2.0], 0));
+        assert!(!archive.insert("b", vec![3.0, 3.0], 0));
+        assert!(!archive.insert("c", vec![2.0, 2.0], 0));
+        assert!(archive.insert("d", vec![1.0, 3.0], 1));
+        assert!(archive.insert("e", vec![1.0, 1.0], 2));
+        assert_eq!(archive.len(), 1);
+        assert_eq!(archive.entries()[0].unit, "e");
+
+        let mut archive = ParetoArchive::new(vec![Minimize, Minimize]).with_epsilon(vec![1.0, 1.0]);
+        for i in 0..=40 {
+            let x = i as f64 / 10.0;
+            archive.insert(i, vec![x, 4.0 - x], 0);
+        }
+        // One unit is kept per box along the front, being the nearest to the
+        // best corner of its box.
+        let mut units: Vec<usize> = archive.entries().iter().map(|e| e.unit).collect();
+        units.sort();
+        assert_eq!(units, vec![5, 15, 25, 35]);
+
+        let mut population = MultiObjectivePopulation::new(vec![
+            SchafferUnit { x: -8.0 },
+            SchafferUnit { x: 9.0 },
+        ]);
+        population
+            .set_size(20)
+            .set_objectives(vec![Minimize, Minimize])
+            .set_archive(ParetoArchive::new(vec![Minimize, Minimize]))
+            .epochs(30);
+        let archive = population.archive().unwrap();
+        assert!(archive.len() >= 20);
+        for entry in archive.entries() {
+            assert!(!archive
+                .entries()
+                .iter()
+                .any(|other| pareto::dominates(&other.objectives, &entry.objectives, &[Minimize; 2])));
+        }
+    }
+
+    #[test]
+    fn multi_objective_algorithms_test() {
+        let algorithms = [
+            Algorithm::Nsga2,
+            Algorithm::Spea2 { archive_size: 30 },
+            Algorithm::MoeaD {
+                neighbourhood: 5,
+                decomposition: Decomposition::Tchebycheff,
+            },
+            Algorithm::MoeaD {
+                neighbourhood: 5,
+                decomposition: Decomposition::WeightedSum,
+            },
+        ];
+        let thread_pool = ThreadPool::new(3);
+
+        for algorithm in &algorithms {
+            let run = |thread_pool: Option<&ThreadPool>| {
+                let mut population = MultiObjectivePopulation::new(vec![
+                    SchafferUnit { x: -8.0 },
+                    SchafferUnit { x: 9.0 },
+                ]);
+                population
+                    .set_size(30)
+                    .set_algorithm(*algorithm)
+                    .set_objectives(vec![Objective::Minimize, Objective::Minimize]);
+                if let Some(thread_pool) = thread_pool {
+                    population.set_thread_pool(thread_pool);
+                }
+                population.epochs(60).finish()
+            };
+
+            let solutions = run(None);
+            assert_eq!(solutions.len(), 30, "{:?}", algorithm);
+            let front: Vec<&[f64]> = solutions
+                .iter()
+                .filter(|s| s.rank == 0)
+                .map(|s| s.objectives.as_slice())
+                .collect();
+            assert!(front.len() > 5, "{:?}", algorithm);
+            for solution in solutions.iter().filter(|s| s.rank == 0) {
+                assert!(solution.unit.x > -0.1 && solution.unit.x < 2.1, "{:?}", algorithm);
+            }
+
+            // The true front dominates a hypervolume of a little over 13.3.
+            let volume = pareto::hypervolume(&front, &[4.0, 4.0], &[Objective::Minimize; 2]);
+            assert!(volume > 12.5, "{:?} has a hypervolume of {}", algorithm, volume);
+
+            let parallel = run(Some(&thread_pool));
+            let xs = |solutions: &[multi_objective::Solution<SchafferUnit>]| {
+                solutions.iter().map(|s| s.unit.x).collect::<Vec<f64>>()
+            };
+            assert_eq!(xs(&solutions), xs(&parallel), "{:?}", algorithm);
+        }
+    }
+
+    #[test]
+    fn multi_objective_resize_test() {
+        let algorithms = [
+            Algorithm::Nsga2,
+            Algorithm::Spea2 { archive_size: 10 },
+            Algorithm::MoeaD {
+                neighbourhood: 3,
+                decomposition: Decomposition::Tchebycheff,
+            },
+        ];
+
+        for algorithm in &algorithms {
+            let mut population = MultiObjectivePopulation::new(vec![
+                SchafferUnit { x: -8.0 },
+                SchafferUnit { x: 9.0 },
+            ]);
+            population
+                .set_size(10)
+                .set_algorithm(*algorithm)
+                .set_objectives(vec![Objective::Minimize, Objective::Minimize])
+                .epochs(3);
+
+            population.set_size(20).epochs(3);
+            assert_eq!(population.generations(), 6, "{:?}", algorithm);
+
+            population.set_size(5).epochs(3);
+            assert_eq!(population.generations(), 9, "{:?}", algorithm);
+            let expected = match *algorithm {
+                Algorithm::Spea2 { archive_size } => archive_size,
+                _ => 5,
+            };
+            assert_eq!(population.finish().len(), expected, "{:?}", algorithm);
+        }
+
+        let mut population = MultiObjectivePopulation::new(vec![SchafferUnit { x: 1.0 }]);
+        population.set_size(10);
+        match population.try_set_algorithm(Algorithm::Spea2 { archive_size: 0 }) {
+            Err(PopulationError::InvalidArchiveSize(0)) => (),
+            _ => panic!("expected an invalid archive size"),
+        }
+        for &neighbourhood in &[1, 11] {
+            let algorithm = Algorithm::MoeaD {
+                neighbourhood,
+                decomposition: Decomposition::Tchebycheff,
+            };
+            match population.try_set_algorithm(algorithm) {
+                Err(PopulationError::InvalidNeighbourhood { size: 10, .. }) => (),
+                _ => panic!("expected an invalid neighbourhood"),
+            }
+        }
+        population.set_algorithm(Algorithm::MoeaD {
+            neighbourhood: 10,
+            decomposition: Decomposition::Tchebycheff,
+        });
+        match population.try_set_size(9) {
+            Err(PopulationError::InvalidNeighbourhood { neighbourhood: 10, size: 9 }) => (),
+            _ => panic!("expected an invalid neighbourhood"),
+        }
+    }
+
+    #[test]
+    fn real_vector_test() {
+        let sphere = |x: &[f64]| x.iter().map(|v| (v - 1.0).powi(2)).sum::<f64>();
+        let bounds = vec![(-5.0, 5.0), (-5.0, 5.0), (0.0, 10.0), (1.0, 1.0)];
+        let operators = [
+            (RealCrossover::Sbx { eta: 15.0 }, RealMutation::Polynomial { eta: 20.0 }),
+            (RealCrossover::BlxAlpha { alpha: 0.5 }, RealMutation::Gaussian { sigma: 0.02 }),
+        ];
+
+        for &(crossover, mutation) in &operators {
+            let genome = RealGenome::new(bounds.clone(), sphere)
+                .with_crossover(crossover)
+                .with_mutation(mutation, 0.25);
+            let run = || {
+                let mut rng: StdRng = SeedableRng::from_seed(&[9usize][..]);
+                let mut population = Population::new(genome.random_units(60, &mut rng));
+                population
+                    .set_size(60)
+                    .set_rand_seed(4)
+                    .set_elitism(2)
+                    .set_objective(Objective::Minimize)
+                    .epochs(150);
+                population.finish_ranked()
+            };
+
+            let ranked = run();
+            assert!(
+                ranked[0].fitness < 0.05,
+                "{:?} and {:?} reached {}",
+                crossover,
+                mutation,
+                ranked[0].fitness
+            );
+            assert_eq!(
+                ranked.iter().map(|r| &r.unit).collect::<Vec<_>>(),
+                run().iter().map(|r| &r.unit).collect::<Vec<_>>()
+            );
+        }
+
+        // Without mutation, each child is drawn from the values of its parents
+        // in the way of its crossover.
+        let (a, b) = (vec![-4.0, 2.0, 3.0, 1.0], vec![2.0, -1.0, 7.0, 1.0]);
+        let crossovers = [
+            RealCrossover::Sbx { eta: 2.0 },
+            RealCrossover::BlxAlpha { alpha: 0.5 },
+            RealCrossover::Arithmetic,
+            RealCrossover::Uniform,
+        ];
+        let mut rng: StdRng = SeedableRng::from_seed(&[5usize][..]);
+        for &crossover in &crossovers {
+            let genome = RealGenome::new(bounds.clone(), sphere)
+                .with_crossover(crossover)
+                .with_mutation(RealMutation::Uniform, 0.0);
+            let (x, y) = (genome.unit(a.clone()), genome.unit(b.clone()));
+            for _ in 0..20 {
+                let child = x.breed_with_rng(&y, &mut rng);
+                let values = child.values();
+                match crossover {
+                    RealCrossover::Sbx { .. } => {
+                        assert_eq!(x.breed_with_rng(&x, &mut rng).values(), x.values());
+                    }
+                    RealCrossover::BlxAlpha { alpha } => {
+                        for i in 0..4 {
+                            let extent = alpha * (a[i] - b[i]).abs();
+                            assert!(values[i] >= a[i].min(b[i]) - extent);
+                            assert!(values[i] <= a[i].max(b[i]) + extent);
+                        }
+                    }
+                    RealCrossover::Arithmetic => {
+                        let weight = (values[0] - b[0]) / (a[0] - b[0]);
+                        assert!((0.0..=1.0).contains(&weight));
+                        for i in 1..4 {
+                            let expected = weight * a[i] + (1.0 - weight) * b[i];
+                            assert!((values[i] - expected).abs() < 1e-9);
+                        }
+                    }
+                    RealCrossover::Uniform => {
+                        for i in 0..4 {
+                            assert!(values[i] == a[i] || values[i] == b[i]);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Mutating every value moves each one that is free to move, within its
+        // bounds.
+        let mutations = [
+            RealMutation::Gaussian { sigma: 0.1 },
+            RealMutation::Polynomial { eta: 20.0 },
+            RealMutation::Uniform,
+        ];
+        for &mutation in &mutations {
+            let genome = RealGenome::new(bounds.clone(), sphere).with_mutation(mutation, 1.0);
+            let parent = genome.unit(a.clone());
+            for _ in 0..20 {
+                let child = parent.breed_with_rng(&parent, &mut rng);
+                for (i, &(low, high)) in bounds.iter().enumerate() {
+                    let value = child.values()[i];
+                    assert!(value >= low && value <= high, "{:?}", mutation);
+                    assert_eq!(value == a[i], low == high, "{:?}", mutation);
+                }
+            }
+        }
+
+        let clamped = RealGenome::new(bounds.clone(), sphere).unit(vec![-9.0, 0.5, 11.0, 0.0]);
+        assert_eq!(clamped.values(), &[-5.0, 0.5, 10.0, 1.0]);
+        assert_eq!(clamped.fitness(), 36.0 + 0.25 + 81.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn real_vector_invalid_mutation_test() {
+        RealGenome::new(vec![(0.0, 1.0)], |x: &[f64]| x[0])
+            .with_mutation(RealMutation::Gaussian { sigma: -0.1 }, 0.5);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bit_string_invalid_crossover_test() {
+        BitGenome::new(8, |bits: &::genome::bit_string::BitString| bits.len() as f64)
+            .with_crossover(BitCrossover::NPoint(0));
+    }
+
+    #[test]
+    fn bit_string_test() {
+        let one_max = |bits: &::genome::bit_string::BitString| bits.count_ones() as f64;
+        let genome = BitGenome::new(150, one_max);
+        let run = || {
+            let mut rng: StdRng = SeedableRng::from_seed(&[3usize][..]);
+            let mut population = Population::new(genome.random_units(50, &mut rng));
+            population.set_size(50).set_rand_seed(8).set_elitism(2).epochs(150);
+            population.finish_ranked()
+        };
+
+        let ranked = run();
+        assert!(ranked[0].fitness >= 140.0, "reached {}", ranked[0].fitness);
+        assert_eq!(
+            ranked.iter().map(|r| &r.unit).collect::<Vec<_>>(),
+            run().iter().map(|r| &r.unit).collect::<Vec<_>>()
+        );
+
+        let operators = [
+            BitCrossover::OnePoint,
+            BitCrossover::TwoPoint,
+            BitCrossover::Uniform,
+            BitCrossover::NPoint(5),
+        ];
+        for &crossover in &operators {
+            // Crossing all zeros with all ones without mutation switches between
+            // the parents no more often than the number of cut points.
+            let genome = genome.clone().with_crossover(crossover).with_mutation_rate(0.0);
+            let zeros = genome.zeros();
+            let ones = genome.unit(vec![true; 150]);
+            let mut rng: StdRng = SeedableRng::from_seed(&[5usize][..]);
+            for _ in 0..20 {
+                let child = zeros.breed_with_rng(&ones, &mut rng);
+                let bits: Vec<bool> = child.iter().collect();
+                let switches = bits.windows(2).filter(|w| w[0] != w[1]).count();
+                match crossover {
+                    BitCrossover::OnePoint => {
+                        assert_eq!(switches, 1);
+                        assert!(!bits[0] && bits[149]);
+                    }
+                    BitCrossover::TwoPoint => {
+                        assert!(switches == 2 || switches == 1);
+                        assert!(!bits[0]);
+                        assert_eq!(bits[149], switches == 1);
+                    }
+                    BitCrossover::NPoint(n) => assert!(switches >= 1 && switches <= n),
+                    BitCrossover::Uniform => {
+                        assert!(switches > 10);
+                        assert!(child.count_ones() > 45 && child.count_ones() < 105);
+                    }
+                }
+                assert_eq!(child.ones().count(), child.count_ones());
+            }
+        }
+
+        // Long strings are packed, and sparse mutation flips few bits.
+        let genome = BitGenome::new(100_000, one_max).with_mutation_rate(0.001);
+        let zeros = genome.zeros();
+        assert_eq!(zeros.words().len(), 1563);
+        let mut rng: StdRng = SeedableRng::from_seed(&[1usize][..]);
+        let child = zeros.breed_with_rng(&zeros, &mut rng);
+        assert!(child.count_ones() > 50 && child.count_ones() < 150);
+        assert_eq!(child.words()[1562] >> 32, 0);
+
+        let full = genome.clone().with_mutation_rate(1.0);
+        let child = full.zeros().breed_with_rng(&full.zeros(), &mut rng);
+        assert_eq!(child.count_ones(), 100_000);
+        assert_eq!(child.fitness(), 100_000.0);
+
+        let mut unit = BitGenome::new(3, one_max).unit(vec![true, false, true]);
+        unit.flip(1);
+        unit.set(0, false);
+        assert_eq!(unit.iter().collect::<Vec<_>>(), vec![false, true, true]);
+        assert_eq!(format!("{:?}", unit), "BitString(011)");
+    }
+
+    #[test]
+    fn permutation_test() {
+        // A tour of points evenly spaced around a unit circle, which is
+        // shortest when visiting them in order around the circle.
+        let n = 16;
+        let point = move |i: usize| {
+            let angle = i as f64 * 2.0 * ::std::f64::consts::PI / n as f64;
+            (angle.cos(), angle.sin())
+        };
+        let tour = move |order: &[usize]| {
+            (0..order.len())
+                .map(|i| {
+                    let (a, b) = (point(order[i]), point(order[(i + 1) % order.len()]));
+                    ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+                })
+                .sum::<f64>()
+        };
+        let shortest = tour(&(0..n).collect::<Vec<_>>());
+
+        for &crossover in &[PermutationCrossover::Order, PermutationCrossover::EdgeRecombination] {
+            let genome = PermutationGenome::new(n, tour).with_crossover(crossover);
+            let run = || {
+                let mut rng: StdRng = SeedableRng::from_seed(&[2usize][..]);
+                let mut population = Population::new(genome.random_units(60, &mut rng));
+                population
+                    .set_size(60)
+                    .set_rand_seed(6)
+                    .set_elitism(2)
+                    .set_objective(Objective::Minimize)
+                    .epochs(200);
+                population.finish_ranked()
+            };
+
+            let ranked = run();
+            assert!(
+                ranked[0].fitness < shortest * 1.02,
+                "{:?} reached {} of {}",
+                crossover,
+                ranked[0].fitness,
+                shortest
+            );
+            assert_eq!(
+                ranked.iter().map(|r| &r.unit).collect::<Vec<_>>(),
+                run().iter().map(|r| &r.unit).collect::<Vec<_>>()
+            );
+        }
+
+        // Every pairing of operators produces a valid permutation.
+        let crossovers = [
+            PermutationCrossover::Order,
+            PermutationCrossover::PartiallyMapped,
+            PermutationCrossover::Cycle,
+            PermutationCrossover::EdgeRecombination,
+        ];
+        let mutations = [
+            PermutationMutation::Swap,
+            PermutationMutation::Insert,
+            PermutationMutation::Inversion,
+            PermutationMutation::Scramble,
+        ];
+        for &crossover in &crossovers {
+            for &mutation in &mutations {
+                let genome = PermutationGenome::new(n, tour)
+                    .with_crossover(crossover)
+                    .with_mutation(mutation, 1.0);
+                let mut rng: StdRng = SeedableRng::from_seed(&[7usize][..]);
+                let parents = genome.random_units(10, &mut rng);
+                for pair in parents.windows(2) {
+                    let child = pair[0].breed_with_rng(&pair[1], &mut rng);
+                    let mut sorted = child.clone().into_order();
+                    sorted.sort();
+                    assert_eq!(sorted, (0..n).collect::<Vec<_>>());
+                }
+            }
+        }
+
+        // Without mutation, order and partially mapped crossover keep a segment
+        // of the first parent in place. Order crossover fills the rest with
+        // the missing items in the order they follow the segment in the second
+        // parent, while partially mapped crossover keeps each item of the
+        // second parent that is not in the segment at its position.
+        let a: Vec<usize> = (0..8).collect();
+        let b = vec![3, 7, 0, 5, 1, 6, 2, 4];
+        let keeps_segment = |crossover: PermutationCrossover, child: &[usize]| {
+            (0..8).any(|start| {
+                (start + 1..9).any(|end| {
+                    let segment = &a[start..end];
+                    if child[start..end] != *segment {
+                        return false;
+                    }
+                    let outside = (end..8).chain(0..start);
+                    match crossover {
+                        PermutationCrossover::Order => {
+                            let rest: Vec<usize> = outside.map(|i| child[i]).collect();
+                            let missing: Vec<usize> = b[end..]
+                                .iter()
+                                .chain(&b[..end])
+                                .cloned()
+                                .filter(|item| !segment.contains(item))
+                                .collect();
+                            rest == missing
+                        }
+                        _ => outside
+                            .filter(|&i| !segment.contains(&b[i]))
+                            .all(|i| child[i] == b[i]),
+                    }
+                })
+            })
+        };
+        let mut rng: StdRng = SeedableRng::from_seed(&[3usize][..]);
+        for &crossover in &[PermutationCrossover::Order, PermutationCrossover::PartiallyMapped] {
+            let genome = PermutationGenome::new(8, tour)
+                .with_crossover(crossover)
+                .with_mutation(PermutationMutation::Swap, 0.0);
+            let (x, y) = (genome.unit(a.clone()), genome.unit(b.clone()));
+            for _ in 0..20 {
+                let child = x.breed_with_rng(&y, &mut rng);
+                assert!(keeps_segment(crossover, child.order()), "{:?}", child.order());
+            }
+        }
+
+        // Edge recombination of two orders that describe the same closed tour
+        // only follows edges of that tour.
+        let genome = PermutationGenome::new(8, tour)
+            .with_crossover(PermutationCrossover::EdgeRecombination)
+            .with_mutation(PermutationMutation::Swap, 0.0);
+        let x = genome.unit(a.clone());
+        let y = genome.unit(vec![5, 4, 3, 2, 1, 0, 7, 6]);
+        for _ in 0..20 {
+            let child = x.breed_with_rng(&y, &mut rng);
+            for pair in child.order().windows(2) {
+                let gap = (pair[0] + 8 - pair[1]) % 8;
+                assert!(gap == 1 || gap == 7, "{:?}", child.order());
+            }
+        }
+
+        // Without mutation, cycle crossover keeps each item at its position in
+        // one of the parents.
+        let genome = PermutationGenome::new(8, tour)
+            .with_crossover(PermutationCrossover::Cycle)
+            .with_mutation(PermutationMutation::Swap, 0.0);
+        let a = genome.unit(vec![0, 1, 2, 3, 4, 5, 6, 7]);
+        let b = genome.unit(vec![1, 2, 0, 4, 3, 6, 7, 5]);
+        let mut rng: StdRng = SeedableRng::from_seed(&[1usize][..]);
+        let child = a.breed_with_rng(&b, &mut rng);
+        assert_eq!(child.order(), &[0, 1, 2, 4, 3, 5, 6, 7]);
+        assert_eq!(genome.identity().order(), a.order());
+    }
+
+    #[test]
+    fn mixed_genome_test() {
+        let genome = MixedGenome::new(|c| {
+            let mut score = -((c.int("layers") - 5) as f64).powi(2);
+            score -= 100.0 * (c.float("rate") - 0.03).powi(2);
+            if c.category("optimizer") == "adam" {
+                score += 3.0;
+            }
+            if c.bool("dropout") {
+                score += 1.0;
+            }
+            score
+        }).with_int("layers", 1, 12)
+            .with_float("rate", 0.0, 1.0)
+            .with_categorical("optimizer", &["sgd", "adam", "rmsprop"])
+            .with_bool("dropout");
+
+        let run = || {
+            let mut rng: StdRng = SeedableRng::from_seed(&[4usize][..]);
+            let mut population = Population::new(genome.random_units(40, &mut rng));
+            population.set_size(40).set_rand_seed(3).set_elitism(2).epochs(100);
+            population.finish_ranked()
+        };
+
+        let ranked = run();
+        let best = &ranked[0].unit;
+        assert_eq!(best.int("layers"), 5);
+        assert!((best.float("rate") - 0.03).abs() < 0.02);
+        assert_eq!(best.category("optimizer"), "adam");
+        assert!(best.bool("dropout"));
+        for r in &ranked {
+            let layers = r.unit.int("layers");
+            let rate = r.unit.float("rate");
+            assert!((1..=12).contains(&layers) && (0.0..=1.0).contains(&rate));
+        }
+        assert_eq!(
+            ranked.iter().map(|r| &r.unit).collect::<Vec<_>>(),
+            run().iter().map(|r| &r.unit).collect::<Vec<_>>()
+        );
+
+        // Without mutation, uniform crossover takes every gene from a parent,
+        // while blend crossover only does so for genes that are not numeric.
+        let a = vec![Value::Int(2), Value::Float(0.2), Value::Categorical(0), Value::Bool(false)];
+        let b = vec![Value::Int(8), Value::Float(0.6), Value::Categorical(2), Value::Bool(true)];
+        let mut rng: StdRng = SeedableRng::from_seed(&[6usize][..]);
+        for &crossover in &[MixedCrossover::Blend { alpha: 0.5 }, MixedCrossover::Uniform] {
+            let genome = genome.clone().with_crossover(crossover).with_mutation(0.1, 0.0);
+            let (x, y) = (genome.unit(a.clone()), genome.unit(b.clone()));
+            let mut blended = false;
+            for _ in 0..20 {
+                let child = x.breed_with_rng(&y, &mut rng);
+                for (i, &value) in child.values().iter().enumerate() {
+                    match (crossover, value) {
+                        (MixedCrossover::Blend { .. }, Value::Int(v)) => {
+                            assert!((1..=11).contains(&v));
+                            blended |= v != 2 && v != 8;
+                        }
+                        (MixedCrossover::Blend { .. }, Value::Float(v)) => {
+                            assert!((0.0..=0.8).contains(&v));
+                            blended |= v != 0.2 && v != 0.6;
+                        }
+                        _ => assert!(value == a[i] || value == b[i], "{:?}", crossover),
+                    }
+                }
+            }
+            assert_eq!(blended, crossover != MixedCrossover::Uniform);
+        }
+
+        // Mutation changes every gene, keeping numeric genes in range.
+        let narrow = MixedGenome::new(|_| 0.0)
+            .with_int("n", 0, 1)
+            .with_categorical("c", &["a", "b"])
+            .with_bool("b")
+            .with_mutation(0.1, 1.0);
+        let unit = narrow.unit(vec![Value::Int(1), Value::Categorical(0), Value::Bool(false)]);
+        let mut rng: StdRng = SeedableRng::from_seed(&[2usize][..]);
+        let child = unit.breed_with_rng(&unit, &mut rng);
+        assert_eq!(child.values(), &[Value::Int(0), Value::Categorical(1), Value::Bool(true)]);
+        assert_eq!(child.get("c"), Some(Value::Categorical(1)));
+        assert_eq!(child.get("missing"), None);
+
+        let clamped = genome.unit(vec![
+            Value::Int(20),
+            Value::Float(-1.0),
+            Value::Categorical(2),
+            Value::Bool(false),
+        ]);
+        assert_eq!(clamped.int("layers"), 12);
+        assert_eq!(clamped.float("rate"), 0.0);
+        assert_eq!(clamped.category("optimizer"), "rmsprop");
+        assert_eq!(genome.genes().nth(3), Some(("dropout", &Gene::Bool)));
+    }
 }
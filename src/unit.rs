@@ -18,17 +18,98 @@
 // OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN
 // THE SOFTWARE.
 
+use fitness::Fitness;
+
+use rand::Rng;
+
+use std::error::Error;
+
 /// Unit is an abstraction for representing a discrete set of variables to test
 /// against a fitness function, and producing children by mutating those
 /// variables.
-pub trait Unit: Send {
-    /// Calculate the relative fitness of this Unit by performing a task using
-    /// its variables. Should return a value between 0 and 1, where 1 would be
-    /// the maximum success and 0 is utter failure.
-    fn fitness(&self) -> f64;
+pub trait Unit: Send + Sized {
+    /// The type of fitness this unit is ranked by, which is usually `f64`, in
+    /// which case implementing `ScalarUnit` instead avoids naming it. Any type
+    /// implementing `Fitness` can be used, such as an integer score or a tuple
+    /// of objectives that are ranked lexicographically.
+    type Fitness: Fitness;
+
+    /// Calculate the fitness of this Unit by performing a task using its
+    /// variables. Fitness can be on any scale, where greater is better unless
+    /// the population is set to `Objective::Minimize`, in which case lesser is
+    /// better.
+    fn fitness(&self) -> Self::Fitness;
+
+    /// Calculate the fitness of this Unit in the same way as `fitness`, but
+    /// allowing the calculation to fail. Failures, along with panics, are
+    /// handled according to the `FailurePolicy` of the population. Units that
+    /// override this method can implement `fitness` by panicking on failure.
+    ///
+    /// By default this calls `fitness`.
+    fn try_fitness(&self) -> Result<Self::Fitness, Box<dyn Error + Send + Sync>> {
+        Ok(self.fitness())
+    }
 
     /// Create a new unit by merging variable qualities from this and one other
     /// unit. The offspring should occasionally experience mutation in random
     /// dimensions.
     fn breed_with(&self, other: &Self) -> Self;
+
+    /// Create a new unit in the same way as `breed_with`, but drawing all
+    /// randomness from `rng`. The population provides a generator derived from
+    /// its seed for each child, and therefore units overriding this method
+    /// produce identical runs for identical seeds, regardless of the number of
+    /// threads used. Such units can implement `breed_with` by calling this
+    /// method with `rand::thread_rng()`.
+    ///
+    /// By default this ignores `rng` and calls `breed_with`.
+    fn breed_with_rng<R: Rng>(&self, other: &Self, _rng: &mut R) -> Self {
+        self.breed_with(other)
+    }
+}
+
+/// ScalarUnit is a helper for the usual case of a unit ranked by a single
+/// `f64` fitness. Every type implementing it implements `Unit` with an `f64`
+/// fitness, and so such units need not name their fitness type.
+pub trait ScalarUnit: Send + Sized {
+    /// Calculate the fitness of this unit in the same way as `Unit::fitness`.
+    fn fitness(&self) -> f64;
+
+    /// Calculate the fitness of this unit in the same way as
+    /// `Unit::try_fitness`.
+    ///
+    /// By default this calls `fitness`.
+    fn try_fitness(&self) -> Result<f64, Box<dyn Error + Send + Sync>> {
+        Ok(self.fitness())
+    }
+
+    /// Create a new unit in the same way as `Unit::breed_with`.
+    fn breed_with(&self, other: &Self) -> Self;
+
+    /// Create a new unit in the same way as `Unit::breed_with_rng`.
+    ///
+    /// By default this ignores `rng` and calls `breed_with`.
+    fn breed_with_rng<R: Rng>(&self, other: &Self, _rng: &mut R) -> Self {
+        self.breed_with(other)
+    }
+}
+
+impl<T: ScalarUnit> Unit for T {
+    type Fitness = f64;
+
+    fn fitness(&self) -> f64 {
+        ScalarUnit::fitness(self)
+    }
+
+    fn try_fitness(&self) -> Result<f64, Box<dyn Error + Send + Sync>> {
+        ScalarUnit::try_fitness(self)
+    }
+
+    fn breed_with(&self, other: &Self) -> Self {
+        ScalarUnit::breed_with(self, other)
+    }
+
+    fn breed_with_rng<R: Rng>(&self, other: &Self, rng: &mut R) -> Self {
+        ScalarUnit::breed_with_rng(self, other, rng)
+    }
 }